@@ -0,0 +1,43 @@
+//! How the game API acts on verdicts from the anti-cheat backend.
+
+use std::fmt;
+
+/// What the game API does with anti-cheat verdicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnticheatEnforcementMode {
+  /// Do not call the anti-cheat backend.
+  #[default]
+  Off,
+  /// Report verdicts without acting on them, to tune detection.
+  Log,
+  /// Act on verdicts, kicking or banning flagged players.
+  Enforce,
+}
+
+impl AnticheatEnforcementMode {
+  /// Parses the environment variable form (`"off"`, `"log"`, `"enforce"`),
+  /// returning `None` for anything else.
+  pub fn parse(value: &str) -> Option<AnticheatEnforcementMode> {
+    match value {
+      "off" => Some(AnticheatEnforcementMode::Off),
+      "log" => Some(AnticheatEnforcementMode::Log),
+      "enforce" => Some(AnticheatEnforcementMode::Enforce),
+      _ => None,
+    }
+  }
+
+  /// Returns the environment variable form of the mode.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AnticheatEnforcementMode::Off => "off",
+      AnticheatEnforcementMode::Log => "log",
+      AnticheatEnforcementMode::Enforce => "enforce",
+    }
+  }
+}
+
+impl fmt::Display for AnticheatEnforcementMode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
@@ -0,0 +1,46 @@
+//! Static API keys for machine clients, redacted when formatted.
+
+use std::fmt;
+
+use crate::hash;
+
+/// A secret API key. `Debug` prints `ApiKey(<redacted>)` so keys do not leak
+/// into logs; use [`ApiKey::expose`] to read the value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+  pub fn new(key: &str) -> Self {
+    ApiKey(key.to_string())
+  }
+
+  /// Returns the secret value.
+  pub fn expose(&self) -> &str {
+    &self.0
+  }
+
+  /// Compares `candidate` with the key in constant time.
+  pub fn verify(&self, candidate: &str) -> bool {
+    hash::constant_time_eq(self.0.as_bytes(), candidate.as_bytes())
+  }
+}
+
+impl fmt::Debug for ApiKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("ApiKey(<redacted>)")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_api_key() {
+    let key = ApiKey::new("s3cret");
+    assert_eq!(format!("{key:?}"), "ApiKey(<redacted>)");
+    assert_eq!(key.expose(), "s3cret");
+    assert!(key.verify("s3cret"));
+    assert!(!key.verify("s3cre"));
+  }
+}
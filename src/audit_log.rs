@@ -0,0 +1,43 @@
+//! Destination of the audit log written for compliance.
+
+use std::fmt;
+
+/// Where audit log records are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AuditLogSink {
+  /// Append to the file at `audit_log_path`.
+  #[default]
+  File,
+  /// Write to standard output, for log collectors that scrape containers.
+  Stdout,
+  /// Send to the local syslog daemon.
+  Syslog,
+}
+
+impl AuditLogSink {
+  /// Parses the environment variable form (`"file"`, `"stdout"`,
+  /// `"syslog"`), returning `None` for anything else.
+  pub fn parse(value: &str) -> Option<AuditLogSink> {
+    match value {
+      "file" => Some(AuditLogSink::File),
+      "stdout" => Some(AuditLogSink::Stdout),
+      "syslog" => Some(AuditLogSink::Syslog),
+      _ => None,
+    }
+  }
+
+  /// Returns the environment variable form of the sink.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditLogSink::File => "file",
+      AuditLogSink::Stdout => "stdout",
+      AuditLogSink::Syslog => "syslog",
+    }
+  }
+}
+
+impl fmt::Display for AuditLogSink {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
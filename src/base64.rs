@@ -0,0 +1,92 @@
+//! Minimal base64 encoding (RFC 4648).
+
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `data` with the URL-safe alphabet and no padding.
+pub(crate) fn encode_url_safe_no_pad(data: &[u8]) -> String {
+  encode_with(URL_SAFE, data)
+}
+
+/// Encodes `data` with the standard alphabet and `=` padding.
+#[cfg(any(test, feature = "etcd"))]
+pub(crate) fn encode(data: &[u8]) -> String {
+  let mut out = encode_with(STANDARD, data);
+  while !out.len().is_multiple_of(4) {
+    out.push('=');
+  }
+  out
+}
+
+/// Decodes standard base64, with or without padding. Returns `None` for
+/// characters outside the alphabet or a truncated final group.
+pub(crate) fn decode(encoded: &str) -> Option<Vec<u8>> {
+  let digits = encoded
+    .trim_end_matches('=')
+    .bytes()
+    .map(|c| STANDARD.iter().position(|&d| d == c).map(|i| i as u32))
+    .collect::<Option<Vec<u32>>>()?;
+  if digits.len() % 4 == 1 {
+    return None;
+  }
+  let mut out = Vec::with_capacity(digits.len() * 3 / 4);
+  for chunk in digits.chunks(4) {
+    let n = chunk
+      .iter()
+      .enumerate()
+      .fold(0, |n, (i, digit)| n | (digit << (18 - 6 * i)));
+    for i in 0..chunk.len() - 1 {
+      out.push((n >> (16 - 8 * i)) as u8);
+    }
+  }
+  Some(out)
+}
+
+fn encode_with(alphabet: &[u8; 64], data: &[u8]) -> String {
+  let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+  for chunk in data.chunks(3) {
+    let b = [
+      chunk[0],
+      chunk.get(1).copied().unwrap_or(0),
+      chunk.get(2).copied().unwrap_or(0),
+    ];
+    let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+    for i in 0..=chunk.len() {
+      out.push(alphabet[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_url_safe_no_pad() {
+    assert_eq!(encode_url_safe_no_pad(b""), "");
+    assert_eq!(encode_url_safe_no_pad(b"f"), "Zg");
+    assert_eq!(encode_url_safe_no_pad(b"fo"), "Zm8");
+    assert_eq!(encode_url_safe_no_pad(b"foo"), "Zm9v");
+    assert_eq!(encode_url_safe_no_pad(b"foobar"), "Zm9vYmFy");
+    assert_eq!(encode_url_safe_no_pad(&[0xfb, 0xff]), "-_8");
+  }
+
+  #[test]
+  fn test_encode_decode() {
+    for (data, encoded) in [
+      (&b""[..], ""),
+      (b"f", "Zg=="),
+      (b"fo", "Zm8="),
+      (b"foo", "Zm9v"),
+      (b"foobar", "Zm9vYmFy"),
+      (&[0xfb, 0xff], "+/8="),
+    ] {
+      assert_eq!(encode(data), encoded);
+      assert_eq!(decode(encoded).as_deref(), Some(data));
+    }
+    assert_eq!(decode("Zm8").as_deref(), Some(&b"fo"[..]));
+    assert!(decode("Z").is_none());
+    assert!(decode("Zm9v-_").is_none());
+  }
+}
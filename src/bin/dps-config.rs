@@ -0,0 +1,103 @@
+//! Command-line helpers for local DPS development.
+//!
+//! Usage:
+//! - `dps-config hosts` prints the `/etc/hosts` entries for the current
+//!   configuration.
+//! - `dps-config hosts --install [FILE]` appends the missing entries to
+//!   `FILE` (default `/etc/hosts`).
+//! - `dps-config describe VAR` explains what an environment variable
+//!   configures and the format it expects.
+//! - `dps-config dependencies SERVICE` prints the `HOST:PORT` endpoints
+//!   `SERVICE` depends on, one per line, for entrypoint wait loops.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use dps_config::{DpsConfig, DpsService};
+
+const USAGE: &str =
+  "usage: dps-config hosts [--install [FILE]] | describe VAR | dependencies SERVICE";
+
+fn main() -> ExitCode {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+  let config = DpsConfig::new();
+
+  let result = match args.as_slice() {
+    ["hosts"] => {
+      for entry in config.get_dev_hosts_entries() {
+        println!("{entry}");
+      }
+      Ok(())
+    }
+    ["hosts", "--install"] => install_hosts(&config, "/etc/hosts"),
+    ["hosts", "--install", file] => install_hosts(&config, file),
+    ["describe", var] => describe(var),
+    ["dependencies", service] => {
+      let Some(service) = DpsService::parse(service) else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+      };
+      for (host, port) in config.dependency_endpoints(&service) {
+        println!("{host}:{port}");
+      }
+      Ok(())
+    }
+    _ => {
+      eprintln!("{USAGE}");
+      return ExitCode::from(2);
+    }
+  };
+
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(err) => {
+      eprintln!("dps-config: {err}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+/// Appends the entries not already present in `file`, ignoring differences in
+/// whitespace.
+fn install_hosts(config: &DpsConfig, file: &str) -> io::Result<()> {
+  let existing = match fs::read_to_string(file) {
+    Ok(contents) => contents,
+    Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+    Err(err) => return Err(err),
+  };
+  let normalize = |line: &str| line.split_whitespace().collect::<Vec<_>>().join(" ");
+  let present: Vec<String> = existing.lines().map(normalize).collect();
+  let missing: Vec<String> = config
+    .get_dev_hosts_entries()
+    .into_iter()
+    .filter(|entry| !present.contains(entry))
+    .collect();
+
+  if missing.is_empty() {
+    println!("{file} is up to date");
+    return Ok(());
+  }
+
+  let mut out = OpenOptions::new().create(true).append(true).open(file)?;
+  if !existing.is_empty() && !existing.ends_with('\n') {
+    writeln!(out)?;
+  }
+  writeln!(out, "# Added by dps-config")?;
+  for entry in &missing {
+    writeln!(out, "{entry}")?;
+    println!("added: {entry}");
+  }
+  Ok(())
+}
+
+fn describe(var: &str) -> io::Result<()> {
+  let meta = DpsConfig::describe(var)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("unknown variable {var}")))?;
+  println!("{var}: {}", meta.description);
+  println!("format: {}", meta.format);
+  println!("default: {}", meta.default.unwrap_or("none"));
+  Ok(())
+}
@@ -0,0 +1,41 @@
+//! Tri-state booleans that keep an explicit `false` apart from "not set".
+
+/// A boolean property as loaded from the environment or set in code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum BoolValue {
+  #[default]
+  Unset,
+  True,
+  False,
+}
+
+impl BoolValue {
+  /// Parses an environment value: `"Y"` is `True`, anything else (such as
+  /// `"N"`) is an explicit `False`.
+  pub(crate) fn parse(value: &str) -> Self {
+    Self::from(value == "Y")
+  }
+
+  /// Returns the value, or `default` when unset.
+  pub(crate) fn unwrap_or(self, default: bool) -> bool {
+    match self {
+      BoolValue::Unset => default,
+      BoolValue::True => true,
+      BoolValue::False => false,
+    }
+  }
+
+  pub(crate) fn is_set(self) -> bool {
+    self != BoolValue::Unset
+  }
+}
+
+impl From<bool> for BoolValue {
+  fn from(value: bool) -> Self {
+    if value {
+      BoolValue::True
+    } else {
+      BoolValue::False
+    }
+  }
+}
@@ -0,0 +1,133 @@
+//! [`config::Source`] adapter for [`DpsConfig`] (`config` feature).
+
+use std::fmt;
+
+use ::config::{Map, Source, Value, ValueKind};
+
+use crate::export::ExportValue;
+use crate::DpsConfig;
+
+/// Name reported as the origin of the values, e.g. in `config` errors.
+const ORIGIN: &str = "DPS config";
+
+/// A [`config::Source`] providing the explicitly set values of a
+/// [`DpsConfig`] (see [`DpsConfig::is_explicitly_set`]), so sources added
+/// before it keep the values DPS does not set.
+///
+/// Keys and value types are the same as for the `figment` provider:
+/// environment variable names without `DPS_` in lowercase
+/// (`auth_api_port`), with per-key variables such as
+/// `DPS_RATE_LIMIT_<ROUTE>` as tables (`rate_limit.login`).
+///
+/// ```rust
+/// use dps_config::{DpsConfig, DpsConfigSource};
+///
+/// let config = DpsConfig::from_map([("DPS_AUTH_API_PORT", "3000")]);
+/// let settings = config::Config::builder()
+///   .set_default("domain", "app.example.com")
+///   .unwrap()
+///   .add_source(DpsConfigSource::from(config))
+///   .build()
+///   .unwrap();
+/// assert_eq!(settings.get::<u16>("auth_api_port").unwrap(), 3000);
+/// assert_eq!(settings.get_string("domain").unwrap(), "app.example.com");
+/// ```
+#[derive(Clone)]
+pub struct DpsConfigSource {
+  config: DpsConfig,
+}
+
+impl DpsConfigSource {
+  /// Creates a source from [`DpsConfig::new`].
+  pub fn new() -> Self {
+    DpsConfig::new().into()
+  }
+}
+
+impl Default for DpsConfigSource {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl From<DpsConfig> for DpsConfigSource {
+  fn from(config: DpsConfig) -> Self {
+    Self { config }
+  }
+}
+
+// `config::Source` requires `Debug`; values are left out so secrets are
+// never printed.
+impl fmt::Debug for DpsConfigSource {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DpsConfigSource").finish_non_exhaustive()
+  }
+}
+
+impl Source for DpsConfigSource {
+  fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+    Box::new(self.clone())
+  }
+
+  fn collect(&self) -> Result<Map<String, Value>, ::config::ConfigError> {
+    Ok(
+      self
+        .config
+        .export()
+        .into_iter()
+        .map(|(key, value)| (key, config_value(value)))
+        .collect(),
+    )
+  }
+}
+
+fn config_value(value: ExportValue) -> Value {
+  let kind = match value {
+    ExportValue::Bool(value) => ValueKind::Boolean(value),
+    ExportValue::Number(value) => ValueKind::U64(value),
+    ExportValue::Float(value) => ValueKind::Float(value),
+    ExportValue::String(value) => ValueKind::String(value),
+    ExportValue::List(values) => ValueKind::Array(values.into_iter().map(config_value).collect()),
+    ExportValue::Table(entries) => ValueKind::Table(
+      entries
+        .into_iter()
+        .map(|(key, value)| (key, config_value(value)))
+        .collect(),
+    ),
+  };
+  Value::new(Some(&ORIGIN.to_string()), kind)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ::config::Config;
+
+  #[test]
+  fn test_collect() {
+    let config = DpsConfig::from_map([
+      ("DPS_DEVELOPMENT_MODE", "Y"),
+      ("DPS_TRACE_SAMPLE_RATIO", "0.25"),
+      ("DPS_DEGRADED_SUBSYSTEMS", "signup,payments"),
+      ("DPS_RATE_LIMIT_LOGIN", "10/min"),
+    ]);
+    let settings = Config::builder()
+      .set_default("domain", "app.example.com")
+      .unwrap()
+      .set_default("development_mode", false)
+      .unwrap()
+      .add_source(DpsConfigSource::from(config))
+      .build()
+      .unwrap();
+    assert_eq!(settings.get_string("domain").unwrap(), "app.example.com");
+    assert!(settings.get_bool("development_mode").unwrap());
+    assert_eq!(settings.get_float("trace_sample_ratio").unwrap(), 0.25);
+    assert_eq!(
+      settings.get::<Vec<String>>("degraded_subsystems").unwrap(),
+      ["signup", "payments"]
+    );
+    assert_eq!(settings.get_int("rate_limit.login.count").unwrap(), 10);
+    assert!(settings.get_string("auth_api_port").is_err());
+    assert!(format!("{:?}", DpsConfigSource::default()).starts_with("DpsConfigSource"));
+  }
+}
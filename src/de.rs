@@ -0,0 +1,396 @@
+//! `envy`-style deserialization of service-specific config structs from
+//! prefixed environment variables (`serde` feature).
+
+use std::fmt;
+
+use serde::de::value::{SeqDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::{env_snapshot, ConfigError};
+
+/// Deserializes `T` from the variables starting with `prefix`, read from the
+/// environment snapshot like [`DpsConfig::new`](crate::DpsConfig::new).
+///
+/// Each variable sets the field named by its lowercase suffix, so with
+/// prefix `"DPS_MYSERVICE_"`, `DPS_MYSERVICE_POOL_SIZE` sets `pool_size`.
+/// Values follow the `DpsConfig` conventions: booleans use `"Y"` for `true`,
+/// lists are comma-separated, enums are unit variants named by the value,
+/// and empty or non-UTF-8 values are treated as unset, so `Option` fields
+/// become `None` and `#[serde(default)]` fields keep their default.
+///
+/// A required field that is not set returns [`ConfigError::Missing`], and a
+/// value that does not parse returns [`ConfigError::InvalidValue`], both
+/// naming the variable.
+///
+/// ```rust
+/// use dps_config::{de, DpsConfig};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct MyService {
+///   workers: u32,
+///   verbose: Option<bool>,
+///   regions: Vec<String>,
+/// }
+///
+/// DpsConfig::replace_env_snapshot([
+///   ("DPS_MYSERVICE_WORKERS", "4"),
+///   ("DPS_MYSERVICE_VERBOSE", ""),
+///   ("DPS_MYSERVICE_REGIONS", "eu, us"),
+/// ]);
+/// let config: MyService = de::from_env_prefix("DPS_MYSERVICE_").unwrap();
+/// assert_eq!(config.workers, 4);
+/// assert_eq!(config.verbose, None);
+/// assert_eq!(config.regions, ["eu", "us"]);
+/// # DpsConfig::reset_env_snapshot();
+/// ```
+pub fn from_env_prefix<T: DeserializeOwned>(prefix: &str) -> Result<T, ConfigError> {
+  let mut entries: Vec<(String, String)> = env_snapshot::get()
+    .iter()
+    .filter_map(|(var, value)| {
+      let (var, value) = (var.to_str()?, value.to_str()?);
+      let set = var.len() > prefix.len() && var.starts_with(prefix) && !value.is_empty();
+      set.then(|| (var.to_string(), value.to_string()))
+    })
+    .collect();
+  entries.sort();
+  let vars = Vars {
+    prefix,
+    entries: entries.into_iter(),
+    current: None,
+  };
+  T::deserialize(vars).map_err(|err| err.into_config_error(prefix))
+}
+
+/// An error before it is reported as a [`ConfigError`].
+#[derive(Debug)]
+enum Error {
+  /// A required field is not set.
+  Missing(&'static str),
+  /// A value was rejected, by the variable it was read from when known.
+  Invalid {
+    var: Option<String>,
+    value: Option<String>,
+    reason: String,
+  },
+}
+
+impl Error {
+  fn invalid(reason: impl Into<String>) -> Self {
+    Error::Invalid {
+      var: None,
+      value: None,
+      reason: reason.into(),
+    }
+  }
+
+  /// Attributes the error to `var` (and its `value`), unless it already is.
+  fn at(self, at_var: &str, at_value: Option<&str>) -> Self {
+    match self {
+      Error::Invalid { var, value, reason } => Error::Invalid {
+        var: var.or_else(|| Some(at_var.to_string())),
+        value: value.or_else(|| at_value.map(str::to_string)),
+        reason,
+      },
+      missing => missing,
+    }
+  }
+
+  fn into_config_error(self, prefix: &str) -> ConfigError {
+    match self {
+      Error::Missing(field) => ConfigError::Missing {
+        var: format!("{prefix}{}", field.to_ascii_uppercase()),
+      },
+      Error::Invalid { var, value, reason } => ConfigError::InvalidValue {
+        var: var.unwrap_or_else(|| prefix.to_string()),
+        value: value.unwrap_or_default(),
+        reason,
+      },
+    }
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Missing(field) => write!(f, "missing field `{field}`"),
+      Error::Invalid { reason, .. } => f.write_str(reason),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+  fn custom<T: fmt::Display>(msg: T) -> Self {
+    Error::invalid(msg.to_string())
+  }
+
+  fn missing_field(field: &'static str) -> Self {
+    Error::Missing(field)
+  }
+}
+
+/// The prefixed variables, read as a map of fields.
+struct Vars<'a> {
+  prefix: &'a str,
+  entries: std::vec::IntoIter<(String, String)>,
+  current: Option<(String, String)>,
+}
+
+impl<'de> de::Deserializer<'de> for Vars<'_> {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_map(self)
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf option unit unit_struct newtype_struct seq tuple
+    tuple_struct map struct enum identifier ignored_any
+  }
+}
+
+impl<'de> MapAccess<'de> for Vars<'_> {
+  type Error = Error;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+    let Some((var, value)) = self.entries.next() else {
+      return Ok(None);
+    };
+    let field = var[self.prefix.len()..].to_ascii_lowercase();
+    let key = StringDeserializer::<Error>::new(field);
+    let key = seed.deserialize(key).map_err(|err| err.at(&var, None))?;
+    self.current = Some((var, value));
+    Ok(Some(key))
+  }
+
+  fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+    let (var, value) = self
+      .current
+      .take()
+      .ok_or_else(|| Error::invalid("value requested before its key"))?;
+    seed
+      .deserialize(Value(value.clone()))
+      .map_err(|err| err.at(&var, Some(&value)))
+  }
+}
+
+/// A single variable's value, or an item of a comma-separated list.
+struct Value(String);
+
+impl Value {
+  fn parse<T: std::str::FromStr>(&self, reason: impl FnOnce() -> String) -> Result<T, Error> {
+    self.0.trim().parse().map_err(|_| Error::invalid(reason()))
+  }
+}
+
+impl IntoDeserializer<'_, Error> for Value {
+  type Deserializer = Self;
+
+  fn into_deserializer(self) -> Self {
+    self
+  }
+}
+
+/// Deserializes integers, with a reason naming the accepted range.
+macro_rules! deserialize_integer {
+  ($($method:ident => $visit:ident $ty:ty,)*) => {
+    $(
+      fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value: $ty = self.parse(|| {
+          format!("must be a number between {} and {}", <$ty>::MIN, <$ty>::MAX)
+        })?;
+        visitor.$visit(value)
+      }
+    )*
+  };
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_string(self.0)
+  }
+
+  fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_bool(self.0 == "Y")
+  }
+
+  deserialize_integer! {
+    deserialize_i8 => visit_i8 i8,
+    deserialize_i16 => visit_i16 i16,
+    deserialize_i32 => visit_i32 i32,
+    deserialize_i64 => visit_i64 i64,
+    deserialize_u8 => visit_u8 u8,
+    deserialize_u16 => visit_u16 u16,
+    deserialize_u32 => visit_u32 u32,
+    deserialize_u64 => visit_u64 u64,
+  }
+
+  fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_f32(self.parse(|| "must be a number".to_string())?)
+  }
+
+  fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_f64(self.parse(|| "must be a number".to_string())?)
+  }
+
+  /// Only set values are deserialized, so options are always `Some`.
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  /// Reads comma-separated items, trimmed and without empty ones.
+  fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let items = self
+      .0
+      .split(',')
+      .map(str::trim)
+      .filter(|item| !item.is_empty())
+      .map(|item| Value(item.to_string()));
+    let mut seq = SeqDeserializer::new(items);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_enum(StringDeserializer::new(self.0))
+  }
+
+  forward_to_deserialize_any! {
+    i128 u128 char str string bytes byte_buf unit unit_struct tuple
+    tuple_struct map struct identifier ignored_any
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::Deserialize;
+  use serial_test::serial;
+
+  use super::*;
+  use crate::DpsConfig;
+
+  #[derive(Debug, PartialEq, Deserialize)]
+  #[serde(rename_all = "lowercase")]
+  enum Mode {
+    Fast,
+    Safe,
+  }
+
+  #[derive(Debug, PartialEq, Deserialize)]
+  struct MyService {
+    name: String,
+    enabled: bool,
+    pool_size: u16,
+    ratio: f64,
+    regions: Vec<String>,
+    mode: Mode,
+    timeout: Option<u32>,
+    verbose: Option<bool>,
+    #[serde(default)]
+    retries: u32,
+  }
+
+  #[test]
+  #[serial]
+  fn test_from_env_prefix() {
+    DpsConfig::replace_env_snapshot([
+      ("DPS_MYSERVICE_NAME", "worker"),
+      ("DPS_MYSERVICE_ENABLED", "Y"),
+      ("DPS_MYSERVICE_POOL_SIZE", "8"),
+      ("DPS_MYSERVICE_RATIO", "0.5"),
+      ("DPS_MYSERVICE_REGIONS", "eu-west, us-east,"),
+      ("DPS_MYSERVICE_MODE", "safe"),
+      ("DPS_MYSERVICE_TIMEOUT", ""),
+      ("DPS_MYSERVICE_VERBOSE", "N"),
+      ("DPS_MYSERVICE_UNKNOWN", "ignored"),
+      ("DPS_OTHER_NAME", "other"),
+    ]);
+    assert_eq!(
+      from_env_prefix::<MyService>("DPS_MYSERVICE_"),
+      Ok(MyService {
+        name: "worker".to_string(),
+        enabled: true,
+        pool_size: 8,
+        ratio: 0.5,
+        regions: vec!["eu-west".to_string(), "us-east".to_string()],
+        mode: Mode::Safe,
+        timeout: None,
+        verbose: Some(false),
+        retries: 0,
+      })
+    );
+
+    DpsConfig::replace_env_snapshot([("DPS_MYSERVICE_NAME", "worker")]);
+    assert_eq!(
+      from_env_prefix::<MyService>("DPS_MYSERVICE_"),
+      Err(ConfigError::Missing {
+        var: "DPS_MYSERVICE_ENABLED".to_string()
+      })
+    );
+    DpsConfig::reset_env_snapshot();
+  }
+
+  #[test]
+  #[serial]
+  fn test_from_env_prefix_invalid_values() {
+    let vars = [
+      ("DPS_MYSERVICE_NAME", "worker"),
+      ("DPS_MYSERVICE_ENABLED", "Y"),
+      ("DPS_MYSERVICE_POOL_SIZE", "8"),
+      ("DPS_MYSERVICE_RATIO", "0.5"),
+      ("DPS_MYSERVICE_REGIONS", "eu-west"),
+      ("DPS_MYSERVICE_MODE", "safe"),
+    ];
+    let invalid = |var: &str, value: &str| {
+      let vars = vars.map(|(name, default)| (name, if name == var { value } else { default }));
+      DpsConfig::replace_env_snapshot(vars);
+      from_env_prefix::<MyService>("DPS_MYSERVICE_").unwrap_err()
+    };
+
+    assert_eq!(
+      invalid("DPS_MYSERVICE_POOL_SIZE", "eight"),
+      ConfigError::InvalidValue {
+        var: "DPS_MYSERVICE_POOL_SIZE".to_string(),
+        value: "eight".to_string(),
+        reason: "must be a number between 0 and 65535".to_string(),
+      }
+    );
+    assert_eq!(
+      invalid("DPS_MYSERVICE_RATIO", "half"),
+      ConfigError::InvalidValue {
+        var: "DPS_MYSERVICE_RATIO".to_string(),
+        value: "half".to_string(),
+        reason: "must be a number".to_string(),
+      }
+    );
+    assert_eq!(
+      invalid("DPS_MYSERVICE_MODE", "slow"),
+      ConfigError::InvalidValue {
+        var: "DPS_MYSERVICE_MODE".to_string(),
+        value: "slow".to_string(),
+        reason: "unknown variant `slow`, expected `fast` or `safe`".to_string(),
+      }
+    );
+    DpsConfig::reset_env_snapshot();
+  }
+}
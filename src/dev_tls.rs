@@ -0,0 +1,46 @@
+//! Self-signed development certificate generation with the `openssl` CLI
+//! (`dev-tls` feature).
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Validity of generated certificates, the longest period browsers accept.
+const VALIDITY_DAYS: &str = "825";
+
+/// Writes a self-signed P-256 certificate for `*.{domain}` and `{domain}` to
+/// `cert_path`, with its private key at `key_path`.
+pub(crate) fn generate_self_signed(
+  domain: &str,
+  cert_path: &Path,
+  key_path: &Path,
+) -> io::Result<()> {
+  for path in [cert_path, key_path] {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+  }
+
+  let output = Command::new("openssl")
+    .args(["req", "-x509", "-nodes", "-days", VALIDITY_DAYS])
+    .args(["-newkey", "ec", "-pkeyopt", "ec_paramgen_curve:prime256v1"])
+    .args(["-subj", &format!("/CN=*.{domain}")])
+    .args([
+      "-addext",
+      &format!("subjectAltName=DNS:*.{domain},DNS:{domain}"),
+    ])
+    .arg("-keyout")
+    .arg(key_path)
+    .arg("-out")
+    .arg(cert_path)
+    .output()?;
+
+  if !output.status.success() {
+    return Err(io::Error::other(format!(
+      "openssl failed to generate a development certificate: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  Ok(())
+}
@@ -0,0 +1,233 @@
+//! Minimal DNS SRV lookup over UDP (RFC 1035, RFC 2782) for the `discovery`
+//! feature.
+//!
+//! Kept in-crate so SRV-based service discovery does not pull a resolver
+//! into every DPS component. Only the first nameserver in
+//! `/etc/resolv.conf` is queried, and truncated responses are not retried
+//! over TCP.
+
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A single SRV record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SrvRecord {
+  pub priority: u16,
+  pub weight: u16,
+  pub port: u16,
+  pub target: String,
+}
+
+/// Looks up `name` (e.g. `_auth-api._tcp.example.com`) and returns the
+/// preferred record: lowest priority, then highest weight.
+pub(crate) fn resolve_srv(name: &str) -> io::Result<Option<SrvRecord>> {
+  let nameserver = system_nameserver();
+  let bind: SocketAddr = match nameserver {
+    SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+    SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+  };
+  let socket = UdpSocket::bind(bind)?;
+  socket.set_read_timeout(Some(TIMEOUT))?;
+  socket.connect(nameserver)?;
+
+  let id = query_id();
+  socket.send(&build_query(id, name)?)?;
+  let mut buf = [0u8; 4096];
+  let len = socket.recv(&mut buf)?;
+  let records = parse_response(id, &buf[..len])?;
+
+  Ok(
+    records
+      .into_iter()
+      .min_by(|a, b| a.priority.cmp(&b.priority).then(b.weight.cmp(&a.weight))),
+  )
+}
+
+/// Returns the first `nameserver` from `/etc/resolv.conf`, or `127.0.0.1`.
+fn system_nameserver() -> SocketAddr {
+  let ip = fs::read_to_string("/etc/resolv.conf")
+    .ok()
+    .and_then(|contents| {
+      contents.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+          Some("nameserver") => parts.next()?.parse::<IpAddr>().ok(),
+          _ => None,
+        }
+      })
+    })
+    .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+  SocketAddr::new(ip, 53)
+}
+
+fn query_id() -> u16 {
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.subsec_nanos())
+    .unwrap_or(0);
+  (nanos ^ std::process::id()) as u16
+}
+
+fn invalid(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn build_query(id: u16, name: &str) -> io::Result<Vec<u8>> {
+  let mut query = Vec::with_capacity(18 + name.len());
+  query.extend_from_slice(&id.to_be_bytes());
+  // Recursion desired, one question
+  query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+  for label in name.trim_end_matches('.').split('.') {
+    if label.is_empty() || label.len() > 63 {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid DNS name {name:?}"),
+      ));
+    }
+    query.push(label.len() as u8);
+    query.extend_from_slice(label.as_bytes());
+  }
+  query.push(0);
+  query.extend_from_slice(&TYPE_SRV.to_be_bytes());
+  query.extend_from_slice(&CLASS_IN.to_be_bytes());
+  Ok(query)
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> io::Result<u16> {
+  buf
+    .get(offset..offset + 2)
+    .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    .ok_or_else(|| invalid("truncated DNS response"))
+}
+
+/// Reads a possibly compressed name at `offset`, returning it and the offset
+/// just past it in the original position.
+fn read_name(buf: &[u8], mut offset: usize) -> io::Result<(String, usize)> {
+  let mut labels = Vec::new();
+  let mut end = None;
+  for _ in 0..128 {
+    let len = *buf
+      .get(offset)
+      .ok_or_else(|| invalid("truncated DNS name"))? as usize;
+    if len & 0xc0 == 0xc0 {
+      let pointer = (read_u16(buf, offset)? & 0x3fff) as usize;
+      end.get_or_insert(offset + 2);
+      offset = pointer;
+    } else if len == 0 {
+      return Ok((labels.join("."), end.unwrap_or(offset + 1)));
+    } else {
+      let label = buf
+        .get(offset + 1..offset + 1 + len)
+        .ok_or_else(|| invalid("truncated DNS label"))?;
+      labels.push(String::from_utf8_lossy(label).into_owned());
+      offset += 1 + len;
+    }
+  }
+  Err(invalid("DNS name compression loop"))
+}
+
+fn parse_response(id: u16, buf: &[u8]) -> io::Result<Vec<SrvRecord>> {
+  if read_u16(buf, 0)? != id {
+    return Err(invalid("DNS response id mismatch"));
+  }
+  let flags = read_u16(buf, 2)?;
+  match flags & 0x000f {
+    0 => {}
+    // NXDOMAIN
+    3 => return Ok(Vec::new()),
+    rcode => return Err(invalid(&format!("DNS server returned rcode {rcode}"))),
+  }
+  let questions = read_u16(buf, 4)?;
+  let answers = read_u16(buf, 6)?;
+
+  let mut offset = 12;
+  for _ in 0..questions {
+    offset = read_name(buf, offset)?.1 + 4;
+  }
+
+  let mut records = Vec::new();
+  for _ in 0..answers {
+    offset = read_name(buf, offset)?.1;
+    let rtype = read_u16(buf, offset)?;
+    let rdlength = read_u16(buf, offset + 8)? as usize;
+    let rdata = offset + 10;
+    if rtype == TYPE_SRV {
+      records.push(SrvRecord {
+        priority: read_u16(buf, rdata)?,
+        weight: read_u16(buf, rdata + 2)?,
+        port: read_u16(buf, rdata + 4)?,
+        target: read_name(buf, rdata + 6)?.0,
+      });
+    }
+    offset = rdata + rdlength;
+  }
+  Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn srv_answer(priority: u16, weight: u16, port: u16, target: &[u8]) -> Vec<u8> {
+    // Name is a pointer to the question name at offset 12
+    let mut answer = vec![0xc0, 12];
+    answer.extend_from_slice(&TYPE_SRV.to_be_bytes());
+    answer.extend_from_slice(&CLASS_IN.to_be_bytes());
+    answer.extend_from_slice(&60u32.to_be_bytes());
+    answer.extend_from_slice(&((6 + target.len()) as u16).to_be_bytes());
+    answer.extend_from_slice(&priority.to_be_bytes());
+    answer.extend_from_slice(&weight.to_be_bytes());
+    answer.extend_from_slice(&port.to_be_bytes());
+    answer.extend_from_slice(target);
+    answer
+  }
+
+  #[test]
+  fn test_build_query() {
+    let query = build_query(0xabcd, "_a._tcp.x").unwrap();
+    assert_eq!(&query[..4], &[0xab, 0xcd, 0x01, 0x00]);
+    assert_eq!(&query[12..], b"\x02_a\x04_tcp\x01x\x00\x00\x21\x00\x01");
+    assert!(build_query(1, "a..b").is_err());
+  }
+
+  #[test]
+  fn test_parse_response() {
+    let mut response = build_query(7, "_auth-api._tcp.example.com").unwrap();
+    response[2] = 0x81;
+    response[3] = 0x80;
+    response[7] = 2;
+    response.extend(srv_answer(10, 5, 3000, b"\x05node1\x07example\x03com\x00"));
+    // Target compressed against the question's "example.com" (offset 27)
+    response.extend(srv_answer(5, 1, 3001, b"\x05node2\xc0\x1b"));
+
+    let records = parse_response(7, &response).unwrap();
+    assert_eq!(
+      records,
+      vec![
+        SrvRecord {
+          priority: 10,
+          weight: 5,
+          port: 3000,
+          target: "node1.example.com".to_string(),
+        },
+        SrvRecord {
+          priority: 5,
+          weight: 1,
+          port: 3001,
+          target: "node2.example.com".to_string(),
+        },
+      ]
+    );
+    assert!(parse_response(8, &response).is_err());
+
+    // NXDOMAIN yields no records
+    response[3] = 0x83;
+    assert_eq!(parse_response(7, &response).unwrap(), Vec::new());
+  }
+}
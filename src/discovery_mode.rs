@@ -0,0 +1,40 @@
+//! How DPS services locate each other's internal endpoints.
+
+use std::fmt;
+
+/// Where internal service endpoints come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DiscoveryMode {
+  /// Use the configured hosts and ports.
+  #[default]
+  Static,
+  /// Look endpoints up in DNS SRV records (requires the `discovery`
+  /// feature).
+  Srv,
+}
+
+impl DiscoveryMode {
+  /// Parses the environment variable form (`"static"`, `"srv"`), returning
+  /// `None` for anything else.
+  pub fn parse(value: &str) -> Option<DiscoveryMode> {
+    match value {
+      "static" => Some(DiscoveryMode::Static),
+      "srv" => Some(DiscoveryMode::Srv),
+      _ => None,
+    }
+  }
+
+  /// Returns the environment variable form of the mode.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      DiscoveryMode::Static => "static",
+      DiscoveryMode::Srv => "srv",
+    }
+  }
+}
+
+impl fmt::Display for DiscoveryMode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
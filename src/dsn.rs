@@ -0,0 +1,134 @@
+//! Parsing `dps://` connection strings such as
+//! `dps://example.com?auth_port=3000&dev=Y` into the `DPS_*` environment
+//! variables read by [`DpsConfig`](crate::DpsConfig).
+
+use std::ffi::OsString;
+
+use crate::env_snapshot::EnvVars;
+use crate::field;
+
+/// Short query keys and the variables they stand for.
+const ALIASES: &[(&str, &str)] = &[
+  ("dev", "DPS_DEVELOPMENT_MODE"),
+  ("auth_port", "DPS_AUTH_API_PORT"),
+];
+
+/// Converts a `dps://` connection string into environment variables: the
+/// host becomes `DPS_DOMAIN` and each `key=value` query pair becomes
+/// `DPS_{KEY}` (e.g. `auth_api_port`), or the variable of a short alias
+/// such as `dev`. Keys already starting with `DPS_`, in any case, name the
+/// variable directly. Keys that name no known variable are rejected. Keys
+/// and values are percent-decoded, with `+` meaning a space.
+pub(crate) fn vars(dsn: &str) -> Result<EnvVars, String> {
+  let rest = dsn
+    .split_once("://")
+    .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("dps"))
+    .map(|(_, rest)| rest)
+    .ok_or("must start with dps://")?;
+  let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+  let host = location.strip_suffix('/').unwrap_or(location);
+  if host.contains(['/', '#']) {
+    return Err("must not have a path or fragment".to_string());
+  }
+
+  let mut vars = EnvVars::new();
+  if !host.is_empty() {
+    vars.insert(OsString::from("DPS_DOMAIN"), OsString::from(decode(host)?));
+  }
+  for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+    let key = decode(key)?;
+    let var = match ALIASES
+      .iter()
+      .find(|(alias, _)| key.eq_ignore_ascii_case(alias))
+    {
+      Some((_, var)) => var.to_string(),
+      None if key.get(..4).is_some_and(|p| p.eq_ignore_ascii_case("DPS_")) => {
+        key.to_ascii_uppercase()
+      }
+      None => format!("DPS_{}", key.to_ascii_uppercase()),
+    };
+    if !is_known(&var) {
+      return Err(format!("unknown key {key:?}"));
+    }
+    vars.insert(OsString::from(var), OsString::from(decode(value)?));
+  }
+  Ok(vars)
+}
+
+/// Returns whether `var` is a config variable, or the `_FILE` variant of a
+/// secret one.
+fn is_known(var: &str) -> bool {
+  match var.strip_suffix("_FILE") {
+    Some(secret) if field::is_secret_var(secret) => true,
+    _ => field::describe(var).is_some(),
+  }
+}
+
+/// Percent-decodes a URL component, turning `+` into a space.
+fn decode(component: &str) -> Result<String, String> {
+  let mut bytes = Vec::with_capacity(component.len());
+  let mut input = component.bytes();
+  while let Some(byte) = input.next() {
+    match byte {
+      b'+' => bytes.push(b' '),
+      b'%' => {
+        let hex = [input.next(), input.next()];
+        let digit = |d: Option<u8>| (d? as char).to_digit(16);
+        match (digit(hex[0]), digit(hex[1])) {
+          (Some(high), Some(low)) => bytes.push((high * 16 + low) as u8),
+          _ => return Err(format!("invalid percent-encoding in {component:?}")),
+        }
+      }
+      _ => bytes.push(byte),
+    }
+  }
+  String::from_utf8(bytes).map_err(|_| format!("{component:?} is not UTF-8 once decoded"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn get<'a>(vars: &'a EnvVars, key: &str) -> Option<&'a str> {
+    vars.get(&OsString::from(key)).and_then(|v| v.to_str())
+  }
+
+  #[test]
+  fn test_vars() {
+    let vars =
+      vars("dps://example.com/?auth_port=3000&dev=Y&auth_api_subdomain=id&dps_Log_Level_Overrides=sqlx%3Dwarn")
+        .unwrap();
+    assert_eq!(get(&vars, "DPS_DOMAIN"), Some("example.com"));
+    assert_eq!(get(&vars, "DPS_AUTH_API_PORT"), Some("3000"));
+    assert_eq!(get(&vars, "DPS_DEVELOPMENT_MODE"), Some("Y"));
+    assert_eq!(get(&vars, "DPS_AUTH_API_SUBDOMAIN"), Some("id"));
+    assert_eq!(get(&vars, "DPS_LOG_LEVEL_OVERRIDES"), Some("sqlx=warn"));
+
+    let vars =
+      super::vars("DPS://?auth_api_allowed_redirect_hosts=a.test,b.test&degraded_subsystems=sign+up&admin_api_token_file=%2Frun%2Ftoken")
+        .unwrap();
+    assert_eq!(get(&vars, "DPS_DOMAIN"), None);
+    assert_eq!(
+      get(&vars, "DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS"),
+      Some("a.test,b.test")
+    );
+    assert_eq!(get(&vars, "DPS_DEGRADED_SUBSYSTEMS"), Some("sign up"));
+    assert_eq!(get(&vars, "DPS_ADMIN_API_TOKEN_FILE"), Some("/run/token"));
+  }
+
+  #[test]
+  fn test_invalid() {
+    assert!(vars("https://example.com").is_err());
+    assert!(vars("example.com").is_err());
+    assert!(vars("dps://example.com/api").is_err());
+    assert!(vars("dps://example.com?dev=%Z1").is_err());
+    assert!(vars("dps://example.com?dev=%FF").is_err());
+    assert_eq!(
+      vars("dps://example.com?auth_prot=3000"),
+      Err("unknown key \"auth_prot\"".to_string())
+    );
+    assert!(vars("dps://example.com?DPS_MOTD=hi").is_err());
+    assert!(vars("dps://example.com?domain_file=%2Fetc%2Fdomain").is_err());
+  }
+}
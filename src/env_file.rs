@@ -0,0 +1,278 @@
+//! Loading `KEY=VALUE` env files into the environment snapshot, with optional
+//! SHA-256 integrity verification shared by all config file formats.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::env_snapshot::{self, EnvVars};
+use crate::{hash, ini, json, toml, yaml, ConfigError};
+
+/// Reads `path`, verifies its checksum when one is available and merges its
+/// variables into the environment snapshot. Variables already present in the
+/// snapshot win.
+pub(crate) fn load(path: &Path) -> Result<(), ConfigError> {
+  merge(read(path)?);
+  Ok(())
+}
+
+/// Merges `file_vars` into the environment snapshot without overriding
+/// variables that are already set.
+pub(crate) fn merge(file_vars: EnvVars) {
+  let mut vars = (*env_snapshot::get()).clone();
+  for (key, value) in file_vars {
+    vars.entry(key).or_insert(value);
+  }
+  env_snapshot::replace(vars);
+}
+
+/// Reads `path` and verifies its checksum when one is available, returning
+/// its variables without touching the environment snapshot.
+pub(crate) fn read(path: &Path) -> Result<EnvVars, ConfigError> {
+  Ok(vars(&read_verified(path)?))
+}
+
+/// Reads `path` as UTF-8 text, verifying its SHA-256 checksum against
+/// `DPS_CONFIG_FILE_SHA256` or an adjacent `{path}.sha256` file when one is
+/// available.
+pub(crate) fn read_verified(path: &Path) -> Result<String, ConfigError> {
+  read_checked(path, true)
+}
+
+/// Reads a config file with `parse` and, when it sets a top-level `extends`
+/// key, the file that names (relative to the file's directory) first, so the
+/// file only overrides what it changes. Bases named `*.toml`, `*.yaml`,
+/// `*.yml`, `*.json` or `*.ini` are parsed as such, others like the file
+/// extending them. Bases may extend further files;
+/// a file extending itself, directly or not, fails with
+/// [`ConfigError::ExtendsCycle`]. Bases are verified against their
+/// `{path}.sha256` files only, since `DPS_CONFIG_FILE_SHA256` covers the
+/// file being loaded; when it is set, a base without one fails with
+/// [`ConfigError::FileRead`] so pinning the file pins the whole chain.
+pub(crate) fn read_extending(
+  path: &Path,
+  parse: fn(&str) -> Result<EnvVars, (usize, &'static str)>,
+) -> Result<EnvVars, ConfigError> {
+  read_chain(path, parse, &mut Vec::new())
+}
+
+/// Reads `path` and its bases for [`read_extending`]. `chain` holds the
+/// canonical paths of the files extending `path`, to detect cycles.
+fn read_chain(
+  path: &Path,
+  parse: fn(&str) -> Result<EnvVars, (usize, &'static str)>,
+  chain: &mut Vec<PathBuf>,
+) -> Result<EnvVars, ConfigError> {
+  let canonical = fs::canonicalize(path).map_err(|err| read_error(path, err))?;
+  if let Some(start) = chain.iter().position(|file| *file == canonical) {
+    let mut paths = chain[start..].to_vec();
+    paths.push(canonical);
+    return Err(ConfigError::ExtendsCycle { paths });
+  }
+  let contents = read_checked(path, chain.is_empty())?;
+  // The checksum covers the encrypted file as committed
+  #[cfg(feature = "sops")]
+  let contents = match crate::sops::is_encrypted(&contents) {
+    true => crate::sops::decrypt(path, &contents)?,
+    false => contents,
+  };
+  let mut vars = parse(&contents).map_err(|(line, reason)| ConfigError::Syntax {
+    path: Some(path.to_path_buf()),
+    line,
+    reason: reason.to_string(),
+  })?;
+  let Some(base) = vars.remove(OsStr::new("DPS_EXTENDS")) else {
+    return Ok(vars);
+  };
+  let base = path.parent().unwrap_or(Path::new("")).join(base);
+  let parse = match base.extension().and_then(OsStr::to_str) {
+    Some("toml") => toml::vars,
+    Some("yaml" | "yml") => yaml::vars,
+    Some("json") => json::vars,
+    Some("ini") => ini::vars,
+    #[cfg(feature = "ron")]
+    Some("ron") => crate::ron::vars,
+    _ => parse,
+  };
+  chain.push(canonical);
+  let mut merged = read_chain(&base, parse, chain)?;
+  chain.pop();
+  merged.extend(vars);
+  Ok(merged)
+}
+
+/// Reads `path` as UTF-8 text, verifying its checksum against
+/// `DPS_CONFIG_FILE_SHA256` (when `use_env` is set) or its sidecar file.
+fn read_checked(path: &Path, use_env: bool) -> Result<String, ConfigError> {
+  let contents = fs::read(path).map_err(|err| read_error(path, err))?;
+
+  if let Some(expected) = expected_checksum(path, use_env)? {
+    let actual: String = hash::sha256(&contents)
+      .iter()
+      .map(|b| format!("{b:02x}"))
+      .collect();
+    if !actual.eq_ignore_ascii_case(&expected) {
+      return Err(ConfigError::ChecksumMismatch {
+        path: path.to_path_buf(),
+        expected,
+        actual,
+      });
+    }
+  }
+
+  String::from_utf8(contents).map_err(|_| ConfigError::FileRead {
+    path: path.to_path_buf(),
+    reason: "not valid UTF-8".to_string(),
+  })
+}
+
+/// Reads a directory where each file holds one variable, named after the
+/// file (Kubernetes ConfigMap and secret mounts). Hidden entries, such as
+/// the `..data` links Kubernetes adds, and subdirectories are skipped;
+/// trailing newlines are stripped from values.
+pub(crate) fn read_dir(path: &Path) -> Result<EnvVars, ConfigError> {
+  let mut vars = EnvVars::new();
+  for entry in fs::read_dir(path).map_err(|err| read_error(path, err))? {
+    let entry = entry.map_err(|err| read_error(path, err))?;
+    let name = entry.file_name();
+    let file = entry.path();
+    if name.to_string_lossy().starts_with('.') || !file.is_file() {
+      continue;
+    }
+    let contents = fs::read_to_string(&file).map_err(|err| read_error(&file, err))?;
+    let value = contents.trim_end_matches(['\n', '\r']);
+    vars.insert(name, OsString::from(value));
+  }
+  Ok(vars)
+}
+
+/// Returns whether `path` looks like an env file: a `.env` extension or a
+/// name starting with `.env` (e.g. `.env.local`).
+pub(crate) fn is_env_file(path: &Path) -> bool {
+  path.extension().is_some_and(|ext| ext == "env")
+    || path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .is_some_and(|name| name.starts_with(".env"))
+}
+
+/// Parses env-file contents into variables.
+pub(crate) fn vars(contents: &str) -> EnvVars {
+  parse(contents)
+    .into_iter()
+    .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+    .collect()
+}
+
+/// Returns the expected hex digest from `DPS_CONFIG_FILE_SHA256` (when
+/// `use_env` is set), or else from an adjacent `{path}.sha256` file in
+/// `sha256sum` format. Without `use_env`, the sidecar file is required
+/// while `DPS_CONFIG_FILE_SHA256` is set.
+fn expected_checksum(path: &Path, use_env: bool) -> Result<Option<String>, ConfigError> {
+  let from_env = env_snapshot::get()
+    .get(OsStr::new("DPS_CONFIG_FILE_SHA256"))
+    .and_then(|v| v.to_str())
+    .filter(|v| !v.is_empty())
+    .map(str::to_string);
+  if use_env && from_env.is_some() {
+    return Ok(from_env);
+  }
+
+  let mut sidecar = path.as_os_str().to_owned();
+  sidecar.push(".sha256");
+  let sidecar = PathBuf::from(sidecar);
+  if !sidecar.is_file() {
+    return match from_env {
+      Some(_) => Err(ConfigError::FileRead {
+        path: sidecar,
+        reason: "required for extended files while DPS_CONFIG_FILE_SHA256 is set".to_string(),
+      }),
+      None => Ok(None),
+    };
+  }
+  let contents = fs::read_to_string(&sidecar).map_err(|err| read_error(&sidecar, err))?;
+  Ok(contents.split_whitespace().next().map(str::to_string))
+}
+
+fn read_error(path: &Path, err: std::io::Error) -> ConfigError {
+  ConfigError::FileRead {
+    path: path.to_path_buf(),
+    reason: err.to_string(),
+  }
+}
+
+/// Parses `KEY=VALUE` lines, skipping blanks and `#` comments and accepting an
+/// optional `export ` prefix.
+fn parse(contents: &str) -> Vec<(&str, String)> {
+  contents
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .filter_map(|line| {
+      let line = line.strip_prefix("export ").unwrap_or(line);
+      let (key, value) = line.split_once('=')?;
+      Some((key.trim(), parse_value(value.trim())))
+    })
+    .collect()
+}
+
+/// Single-quoted values are taken literally, double-quoted ones unescape
+/// `\n`, `\"` and `\\`, and unquoted ones end at a ` #` comment. Text after a
+/// closing quote is ignored.
+fn parse_value(value: &str) -> String {
+  if let Some(rest) = value.strip_prefix('\'') {
+    if let Some((quoted, _)) = rest.split_once('\'') {
+      return quoted.to_string();
+    }
+  }
+  if let Some(rest) = value.strip_prefix('"') {
+    let mut unescaped = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+      match c {
+        '"' => return unescaped,
+        '\\' => match chars.next() {
+          Some('n') => unescaped.push('\n'),
+          Some(c) => unescaped.push(c),
+          None => break,
+        },
+        c => unescaped.push(c),
+      }
+    }
+  }
+  let end = value
+    .char_indices()
+    .find(|&(i, c)| c == '#' && value[..i].ends_with(char::is_whitespace))
+    .map_or(value.len(), |(i, _)| i);
+  value[..end].trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    let contents =
+      "# comment\n\nDPS_DOMAIN=example.com\nexport DPS_API_PATH = \"v1\"\nDPS_X='a b'\ninvalid\n";
+    assert_eq!(
+      parse(contents),
+      vec![
+        ("DPS_DOMAIN", "example.com".to_string()),
+        ("DPS_API_PATH", "v1".to_string()),
+        ("DPS_X", "a b".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_value() {
+    assert_eq!(parse_value("plain # comment"), "plain");
+    assert_eq!(parse_value("a#b"), "a#b");
+    assert_eq!(parse_value("'a # b' # comment"), "a # b");
+    assert_eq!(parse_value(r"'a\nb'"), r"a\nb");
+    assert_eq!(parse_value(r#""a\nb \"c\" \\" # comment"#), "a\nb \"c\" \\");
+    assert_eq!(parse_value("\"unterminated"), "\"unterminated");
+    assert_eq!(parse_value(""), "");
+  }
+}
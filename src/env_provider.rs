@@ -0,0 +1,50 @@
+//! Sources of environment variables that configs can be loaded from.
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+
+/// A source of environment variables for [`DpsConfig::new_with_provider`].
+///
+/// Loading from an injected provider reads neither the process environment
+/// nor the process-wide snapshot, so tests using one are deterministic and
+/// can run in parallel.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use dps_config::DpsConfig;
+///
+/// let env = HashMap::from([("DPS_DOMAIN".to_string(), "example.com".to_string())]);
+/// assert_eq!(DpsConfig::new_with_provider(&env).get_domain(), "example.com");
+/// ```
+///
+/// [`DpsConfig::new_with_provider`]: crate::DpsConfig::new_with_provider
+pub trait EnvProvider {
+  /// Returns every variable the provider holds, like [`std::env::vars_os`].
+  fn vars_os(&self) -> Vec<(OsString, OsString)>;
+}
+
+/// Reads the process environment through [`std::env::vars_os`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdEnv;
+
+impl EnvProvider for StdEnv {
+  fn vars_os(&self) -> Vec<(OsString, OsString)> {
+    env::vars_os().collect()
+  }
+}
+
+impl EnvProvider for HashMap<String, String> {
+  fn vars_os(&self) -> Vec<(OsString, OsString)> {
+    self
+      .iter()
+      .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+      .collect()
+  }
+}
+
+impl EnvProvider for HashMap<OsString, OsString> {
+  fn vars_os(&self) -> Vec<(OsString, OsString)> {
+    self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+  }
+}
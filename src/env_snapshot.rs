@@ -0,0 +1,50 @@
+//! Process-wide snapshot of the environment read by `DpsConfig::new()`.
+//!
+//! The environment is captured once, on first use, so repeated constructions
+//! are cheap and agree with each other. Tests replace the snapshot instead of
+//! mutating the real process environment.
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::sync::{Arc, PoisonError, RwLock};
+
+pub(crate) type EnvVars = HashMap<OsString, OsString>;
+
+static SNAPSHOT: RwLock<Option<Arc<EnvVars>>> = RwLock::new(None);
+
+/// Returns the current snapshot, capturing the environment if there is none.
+pub(crate) fn get() -> Arc<EnvVars> {
+  if let Some(vars) = SNAPSHOT
+    .read()
+    .unwrap_or_else(PoisonError::into_inner)
+    .as_ref()
+  {
+    return vars.clone();
+  }
+  let mut snapshot = SNAPSHOT.write().unwrap_or_else(PoisonError::into_inner);
+  snapshot
+    .get_or_insert_with(|| Arc::new(env::vars_os().collect()))
+    .clone()
+}
+
+/// Replaces the snapshot with `vars`.
+pub(crate) fn replace(vars: EnvVars) {
+  *SNAPSHOT.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(vars));
+}
+
+/// Drops the snapshot so the next use captures the environment again.
+pub(crate) fn reset() {
+  *SNAPSHOT.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Sets (or with `None`, removes) a single variable in the snapshot.
+#[cfg(test)]
+pub(crate) fn set_var(key: &str, value: Option<OsString>) {
+  let mut vars = (*get()).clone();
+  match value {
+    Some(value) => vars.insert(key.into(), value),
+    None => vars.remove(std::ffi::OsStr::new(key)),
+  };
+  replace(vars);
+}
@@ -0,0 +1,139 @@
+//! Error type returned by [`DpsConfig::validate`](crate::DpsConfig::validate),
+//! [`DpsConfig::try_new`](crate::DpsConfig::try_new),
+//! [`DpsConfig::load_env_file`](crate::DpsConfig::load_env_file),
+//! [`DpsConfig::from_toml_file`](crate::DpsConfig::from_toml_file),
+//! [`DpsConfig::from_yaml_file`](crate::DpsConfig::from_yaml_file),
+//! [`DpsConfigLoader::build`](crate::DpsConfigLoader::build) and the
+//! `require_*` accessors, and the non-fatal warnings returned by
+//! [`DpsConfig::warnings`](crate::DpsConfig::warnings).
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors reported when a configured value is missing or not acceptable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+  /// A value was configured but is outside the accepted range or format.
+  ///
+  /// `var` is the environment variable backing the property, `value` is the
+  /// offending value and `reason` explains what was expected.
+  InvalidValue {
+    var: String,
+    value: String,
+    reason: String,
+  },
+  /// A required value was not configured.
+  ///
+  /// `var` is the environment variable backing the property.
+  Missing { var: String },
+  /// An environment variable holds a value that is not valid UTF-8.
+  ///
+  /// Reported by [`DpsConfig::try_new`](crate::DpsConfig::try_new); `var`
+  /// is the variable name.
+  NonUtf8 { var: String },
+  /// An env file could not be read.
+  FileRead { path: PathBuf, reason: String },
+  /// A remote config source, such as an etcd server, could not be read.
+  ///
+  /// `source` identifies the source (e.g. its URL).
+  SourceRead { source: String, reason: String },
+  /// A config file or string is malformed.
+  ///
+  /// `path` is the file, if any, and `line` is 1-based.
+  Syntax {
+    path: Option<PathBuf>,
+    line: usize,
+    reason: String,
+  },
+  /// An env file's SHA-256 digest does not match the expected checksum.
+  ///
+  /// Both digests are lowercase hex; `expected` is as provided.
+  ChecksumMismatch {
+    path: PathBuf,
+    expected: String,
+    actual: String,
+  },
+  /// Config files `extends` each other in a cycle.
+  ///
+  /// `paths` lists the files of the cycle in order, the first one repeated
+  /// at the end.
+  ExtendsCycle { paths: Vec<PathBuf> },
+}
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ConfigError::InvalidValue { var, value, reason } => {
+        write!(f, "{var}={value:?}: {reason}")
+      }
+      ConfigError::Missing { var } => write!(f, "{var} is not set"),
+      ConfigError::NonUtf8 { var } => write!(f, "{var} is not valid UTF-8"),
+      ConfigError::FileRead { path, reason } => {
+        write!(f, "cannot read {}: {reason}", path.display())
+      }
+      ConfigError::SourceRead { source, reason } => {
+        write!(f, "cannot read {source}: {reason}")
+      }
+      ConfigError::Syntax { path, line, reason } => match path {
+        Some(path) => write!(f, "{}:{line}: {reason}", path.display()),
+        None => write!(f, "line {line}: {reason}"),
+      },
+      ConfigError::ChecksumMismatch {
+        path,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "{}: SHA-256 checksum mismatch (expected {expected}, got {actual})",
+        path.display()
+      ),
+      ConfigError::ExtendsCycle { paths } => {
+        let paths: Vec<_> = paths
+          .iter()
+          .map(|path| path.display().to_string())
+          .collect();
+        write!(f, "config files extend each other: {}", paths.join(" -> "))
+      }
+    }
+  }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Non-fatal problems worth surfacing at startup or in monitoring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigWarning {
+  /// A secret expires within the configured warning window.
+  ///
+  /// `var` is the environment variable holding the secret.
+  SecretExpiringSoon {
+    var: &'static str,
+    days_remaining: u64,
+  },
+  /// A secret's expiry time has passed.
+  SecretExpired { var: &'static str },
+  /// A service declares a dependency whose endpoint is not configured
+  /// (see [`DpsConfig::get_service_url`](crate::DpsConfig::get_service_url)),
+  /// such as the image proxy without `DPS_IMAGEPROXY_URL` or `redis`
+  /// without `DPS_SERVICE_URL_REDIS`.
+  UnconfiguredDependency { service: String, dependency: String },
+}
+
+impl fmt::Display for ConfigWarning {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ConfigWarning::SecretExpiringSoon {
+        var,
+        days_remaining,
+      } => write!(f, "{var} expires in {days_remaining} day(s)"),
+      ConfigWarning::SecretExpired { var } => write!(f, "{var} has expired"),
+      ConfigWarning::UnconfiguredDependency {
+        service,
+        dependency,
+      } => write!(
+        f,
+        "{service} depends on {dependency}, which is not configured"
+      ),
+    }
+  }
+}
@@ -0,0 +1,336 @@
+//! Reading `DPS_*` settings from etcd through its v3 JSON gateway with the
+//! `curl` CLI (`etcd` feature).
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{base64, json, DpsConfig};
+
+/// A config layer read from the etcd keys under a prefix.
+///
+/// Each key below the prefix names a variable, with or without the `DPS_`
+/// prefix and in any case; `/` separators become `_`. With prefix `/dps/`,
+/// `/dps/domain`, `/dps/DPS_DOMAIN` and `/dps/auth_api/port` set
+/// `DPS_DOMAIN`, `DPS_DOMAIN` and `DPS_AUTH_API_PORT`.
+///
+/// Add it to a [`DpsConfigLoader`](crate::DpsConfigLoader) with
+/// [`etcd`](crate::DpsConfigLoader::etcd), or keep a config current with
+/// [`EtcdSource::watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EtcdSource {
+  endpoint: String,
+  prefix: String,
+}
+
+impl EtcdSource {
+  /// Creates a source reading the keys under `prefix` from the etcd server
+  /// at `endpoint` (e.g. `http://127.0.0.1:2379`).
+  pub fn new(endpoint: &str, prefix: &str) -> Self {
+    Self {
+      endpoint: endpoint.trim_end_matches('/').to_string(),
+      prefix: prefix.to_string(),
+    }
+  }
+
+  /// Returns the etcd server URL.
+  pub fn endpoint(&self) -> &str {
+    &self.endpoint
+  }
+
+  /// Reads the keys under the prefix into a config that sets only the
+  /// values found in etcd. Fails with the `curl` error when the server
+  /// cannot be reached, and with [`io::ErrorKind::InvalidData`] when the
+  /// response is malformed.
+  pub fn read(&self) -> io::Result<DpsConfig> {
+    Ok(DpsConfig::from_map(self.range()?))
+  }
+
+  /// Polls etcd every `interval` on a background thread and calls
+  /// `on_change` with the config the layer and the environment snapshot
+  /// build together (like `DpsConfigLoader::new().etcd(..).env()`)
+  /// whenever a key under the prefix changes. Failed polls are skipped.
+  ///
+  /// Polling stops when the returned [`EtcdWatch`] is stopped or dropped.
+  pub fn watch(
+    &self,
+    interval: Duration,
+    mut on_change: impl FnMut(DpsConfig) + Send + 'static,
+  ) -> EtcdWatch {
+    let source = self.clone();
+    let stopped = Arc::new(AtomicBool::new(false));
+    let thread = {
+      let stopped = stopped.clone();
+      thread::spawn(move || {
+        let mut last = None;
+        while !stopped.load(Ordering::Relaxed) {
+          let started = Instant::now();
+          if let Ok(mut vars) = source.range() {
+            vars.sort();
+            if last.as_ref() != Some(&vars) {
+              if last.is_some() {
+                let mut config = DpsConfig::from_map(vars.clone());
+                config.overlay(DpsConfig::new());
+                on_change(config);
+              }
+              last = Some(vars);
+            }
+          }
+          while !stopped.load(Ordering::Relaxed) && started.elapsed() < interval {
+            thread::sleep(Duration::from_millis(10).min(interval));
+          }
+        }
+      })
+    };
+    EtcdWatch {
+      stopped,
+      thread: Some(thread),
+    }
+  }
+
+  /// Fetches the keys under the prefix, returning the variables they set.
+  fn range(&self) -> io::Result<Vec<(String, String)>> {
+    let url = format!("{}/v3/kv/range", self.endpoint);
+    let body = format!(
+      r#"{{"key": "{}", "range_end": "{}"}}"#,
+      base64::encode(self.prefix.as_bytes()),
+      base64::encode(&range_end(self.prefix.as_bytes()))
+    );
+    let mut child = Command::new("curl")
+      .args(["--silent", "--show-error", "--fail", "--data-binary", "@-"])
+      .arg(&url)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+      stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+      return Err(io::Error::other(format!(
+        "{url}: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+      )));
+    }
+
+    let invalid =
+      |reason: String| io::Error::new(io::ErrorKind::InvalidData, format!("{url}: {reason}"));
+    let response =
+      String::from_utf8(output.stdout).map_err(|_| invalid("not UTF-8".to_string()))?;
+    let syntax = |(line, reason)| invalid(format!("line {line}: {reason}"));
+    let mut vars = Vec::new();
+    for kv in json::objects_in(&response, "kvs").map_err(syntax)? {
+      let field = |name: &str| {
+        kv.iter()
+          .find(|(key, _)| key == name)
+          .and_then(|(_, value)| String::from_utf8(base64::decode(value)?).ok())
+      };
+      let (Some(key), value) = (field("key"), field("value")) else {
+        return Err(invalid("key is not valid base64 UTF-8".to_string()));
+      };
+      if let Some(var) = var_name(key.strip_prefix(&self.prefix).unwrap_or(&key)) {
+        vars.push((var, value.unwrap_or_default()));
+      }
+    }
+    Ok(vars)
+  }
+}
+
+/// A running [`EtcdSource::watch`]. Dropping it stops polling.
+#[derive(Debug)]
+pub struct EtcdWatch {
+  stopped: Arc<AtomicBool>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl EtcdWatch {
+  /// Stops polling and waits for an in-flight poll to finish.
+  pub fn stop(mut self) {
+    self.shutdown();
+  }
+
+  fn shutdown(&mut self) {
+    self.stopped.store(true, Ordering::Relaxed);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for EtcdWatch {
+  fn drop(&mut self) {
+    self.shutdown();
+  }
+}
+
+/// Returns the end of the key range covering every key starting with
+/// `prefix`: the prefix with its last byte below `0xff` incremented.
+fn range_end(prefix: &[u8]) -> Vec<u8> {
+  let mut end = prefix.to_vec();
+  while let Some(last) = end.pop() {
+    if last < 0xff {
+      end.push(last + 1);
+      return end;
+    }
+  }
+  // Every key: etcd treats "\0" as "no upper bound".
+  vec![0]
+}
+
+/// Maps a key below the prefix to the variable it sets, if it names one.
+fn var_name(key: &str) -> Option<String> {
+  let var = key.trim_matches('/').replace('/', "_").to_ascii_uppercase();
+  match var.is_empty() {
+    true => None,
+    false if var.starts_with("DPS_") => Some(var),
+    false => Some(format!("DPS_{var}")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::DpsConfigLoader;
+  use serial_test::serial;
+  use std::io::{BufRead, BufReader, Read};
+  use std::net::TcpListener;
+  use std::sync::mpsc;
+
+  /// Serves `bodies` as etcd range responses, one connection each, and
+  /// returns the endpoint and a handle yielding the request bodies.
+  fn serve(bodies: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+      let mut requests = Vec::new();
+      for body in bodies {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+          let mut line = String::new();
+          reader.read_line(&mut line).unwrap();
+          if line.trim().is_empty() {
+            break;
+          }
+          if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+            content_length = len.trim().parse().unwrap();
+          }
+        }
+        let mut request = vec![0; content_length];
+        reader.read_exact(&mut request).unwrap();
+        requests.push(String::from_utf8(request).unwrap());
+        write!(
+          reader.get_mut(),
+          "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+          body.len()
+        )
+        .unwrap();
+      }
+      requests
+    });
+    (endpoint, server)
+  }
+
+  fn response(revision: u32, kvs: &[(&str, &str)]) -> String {
+    let kvs: Vec<String> = kvs
+      .iter()
+      .map(|(key, value)| {
+        format!(
+          r#"{{"key": "{}", "value": "{}", "mod_revision": "{revision}"}}"#,
+          base64::encode(key.as_bytes()),
+          base64::encode(value.as_bytes())
+        )
+      })
+      .collect();
+    format!(
+      r#"{{"header": {{"cluster_id": "1", "revision": "{revision}"}}, "kvs": [{}], "count": "{}"}}"#,
+      kvs.join(", "),
+      kvs.len()
+    )
+  }
+
+  #[test]
+  fn test_range_end() {
+    assert_eq!(range_end(b"/dps/"), b"/dps0");
+    assert_eq!(range_end(&[b'a', 0xff]), b"b");
+    assert_eq!(range_end(&[0xff]), [0]);
+  }
+
+  #[test]
+  fn test_var_name() {
+    assert_eq!(var_name("domain").as_deref(), Some("DPS_DOMAIN"));
+    assert_eq!(var_name("DPS_DOMAIN").as_deref(), Some("DPS_DOMAIN"));
+    assert_eq!(
+      var_name("/auth_api/port").as_deref(),
+      Some("DPS_AUTH_API_PORT")
+    );
+    assert_eq!(var_name("/"), None);
+  }
+
+  #[test]
+  #[serial]
+  fn test_read() {
+    let (endpoint, server) = serve(vec![response(
+      3,
+      &[
+        ("/dps/domain", "example.com"),
+        ("/dps/auth_api/port", "8080"),
+      ],
+    )]);
+    let config = EtcdSource::new(&endpoint, "/dps/").read().unwrap();
+    assert_eq!(config.get_domain(), "example.com");
+    assert_eq!(config.get_auth_api_port(), Some(8080));
+    assert_eq!(
+      server.join().unwrap(),
+      vec![r#"{"key": "L2Rwcy8=", "range_end": "L2RwczA="}"#.to_string()]
+    );
+
+    let (endpoint, server) = serve(vec!["not json".to_string()]);
+    let err = EtcdSource::new(&endpoint, "/dps/").read().err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    server.join().unwrap();
+  }
+
+  #[test]
+  #[serial]
+  fn test_loader_and_watch() {
+    let (endpoint, server) = serve(vec![
+      response(
+        3,
+        &[
+          ("/dps/domain", "example.com"),
+          ("/dps/auth_api_port", "8080"),
+        ],
+      ),
+      response(3, &[("/dps/domain", "example.com")]),
+      response(3, &[("/dps/domain", "example.com")]),
+      response(4, &[("/dps/domain", "example.org")]),
+    ]);
+    DpsConfig::replace_env_snapshot([("DPS_AUTH_API_PORT", "3000")]);
+    let source = EtcdSource::new(&endpoint, "/dps/");
+    let config = DpsConfigLoader::new()
+      .env()
+      .etcd(source.clone())
+      .build()
+      .unwrap();
+    assert_eq!(config.get_domain(), "example.com");
+    assert_eq!(config.get_auth_api_port(), Some(3000));
+
+    let (sender, receiver) = mpsc::channel();
+    let watch = source.watch(Duration::from_millis(10), move |config| {
+      sender.send(config).unwrap();
+    });
+    let config = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    watch.stop();
+    assert_eq!(config.get_domain(), "example.org");
+    assert_eq!(config.get_auth_api_port(), Some(3000));
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(server.join().unwrap().len(), 4);
+    DpsConfig::reset_env_snapshot();
+  }
+}
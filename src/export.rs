@@ -0,0 +1,817 @@
+//! Typed values of a [`DpsConfig`] for its `Debug` output and the `figment`,
+//! `config` and `serde` crate integrations.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::field::FIELDS;
+use crate::{rfc3339, DpsConfig, FeatureFlag, Field, RateLimit};
+
+/// Replaces secret values in `Debug` and serialized output.
+pub(crate) const MASK: &str = "***";
+
+/// A config value as exported to other configuration libraries.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ExportValue {
+  Bool(bool),
+  Number(u64),
+  Float(f64),
+  String(String),
+  List(Vec<ExportValue>),
+  /// Entries of a per-key variable such as `DPS_RATE_LIMIT_<ROUTE>`, sorted
+  /// by key.
+  Table(Vec<(String, ExportValue)>),
+}
+
+impl From<bool> for ExportValue {
+  fn from(value: bool) -> Self {
+    ExportValue::Bool(value)
+  }
+}
+
+impl From<u16> for ExportValue {
+  fn from(value: u16) -> Self {
+    ExportValue::Number(value.into())
+  }
+}
+
+impl From<u32> for ExportValue {
+  fn from(value: u32) -> Self {
+    ExportValue::Number(value.into())
+  }
+}
+
+impl From<u64> for ExportValue {
+  fn from(value: u64) -> Self {
+    ExportValue::Number(value)
+  }
+}
+
+impl From<f64> for ExportValue {
+  fn from(value: f64) -> Self {
+    ExportValue::Float(value)
+  }
+}
+
+impl From<String> for ExportValue {
+  fn from(value: String) -> Self {
+    ExportValue::String(value)
+  }
+}
+
+impl From<&str> for ExportValue {
+  fn from(value: &str) -> Self {
+    ExportValue::String(value.to_string())
+  }
+}
+
+impl<T: Into<ExportValue>> From<Vec<T>> for ExportValue {
+  fn from(values: Vec<T>) -> Self {
+    ExportValue::List(values.into_iter().map(Into::into).collect())
+  }
+}
+
+impl DpsConfig {
+  /// Returns the explicitly set values (see
+  /// [`DpsConfig::is_explicitly_set`]), keyed by their environment variable
+  /// name without `DPS_`, in lowercase.
+  #[cfg(any(feature = "figment", feature = "config"))]
+  pub(crate) fn export(&self) -> Vec<(String, ExportValue)> {
+    FIELDS
+      .iter()
+      .filter(|meta| self.is_explicitly_set(meta.field))
+      .filter_map(|meta| {
+        let key = meta.var.trim_start_matches("DPS_").trim_end_matches('_');
+        Some((key.to_ascii_lowercase(), self.export_value(meta.field)?))
+      })
+      .collect()
+  }
+
+  /// Returns the current value of `field`, or `None` for fields that are
+  /// not read from a variable.
+  pub(crate) fn export_value(&self, field: Field) -> Option<ExportValue> {
+    let path = |path: std::path::PathBuf| ExportValue::from(path.to_string_lossy().into_owned());
+    // Storage paths are exported as configured, relative to `data_dir`, so
+    // they read back to the same files
+    let storage = |path: &Option<std::path::PathBuf>, default: &str| match path {
+      Some(path) => path.to_string_lossy().into_owned().into(),
+      None => ExportValue::from(default),
+    };
+    let value = match field {
+      Field::Domain => self.get_domain().into(),
+      Field::ApiPath => self.get_api_path().into(),
+      Field::EnvironmentSubdomainSuffix => self.get_environment_subdomain_suffix()?.into(),
+      Field::DevelopmentMode => self.get_development_mode().into(),
+      Field::Profile => self.get_profile().as_str().into(),
+      Field::DevLocalhostUrls => self.get_dev_localhost_urls().into(),
+      Field::DevTlsAutogenerate => self.get_dev_tls_autogenerate().into(),
+      Field::DevTlsCertDir => storage(&self.dev_tls_cert_dir, "dev-tls"),
+      Field::SecretExpiryWarningDays => self.get_secret_expiry_warning_days().into(),
+      Field::RespectPlatformPort => self.get_respect_platform_port().into(),
+      Field::DataDir => path(self.get_data_dir()),
+      Field::AuthApiSubdomain => self.get_auth_api_subdomain().into(),
+      Field::AuthApiPort => self.get_auth_api_port()?.into(),
+      Field::AuthApiProtocol => self.get_auth_api_protocol().into(),
+      Field::AuthApiInsecureCookie => self.get_auth_api_insecure_cookie().into(),
+      Field::AuthApiSqliteMainFilePath => {
+        storage(&self.auth_api_sqlite_main_file_path, "main-development.db")
+      }
+      Field::AuthApiSqliteMainPoolSize => self.get_auth_api_sqlite_main_pool_size().into(),
+      Field::AuthApiSessionSecret => self.get_auth_api_session_secret()?.into(),
+      Field::AuthApiSessionSecretExpiry => {
+        let expiry = self.get_auth_api_session_secret_expiry()?;
+        let secs = expiry.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+        secs.into()
+      }
+      Field::AuthApiSessionSigningAlgorithm => self
+        .get_auth_api_session_signing_algorithm()
+        .as_str()
+        .into(),
+      Field::AuthApiSessionTtlSeconds => self.get_auth_api_session_ttl_seconds().into(),
+      Field::AuthApiSessionIdleTtlSeconds => self.get_auth_api_session_idle_ttl_seconds().into(),
+      Field::AuthApiRememberMeTtlSeconds => self.get_auth_api_remember_me_ttl_seconds().into(),
+      Field::AuthApiSessionCleanupIntervalSeconds => {
+        self.get_auth_api_session_cleanup_interval_seconds().into()
+      }
+      Field::AuthApiSessionCleanupBatchSize => {
+        self.get_auth_api_session_cleanup_batch_size().into()
+      }
+      Field::AuthApiAllowedRedirectHosts => self.get_auth_api_allowed_redirect_hosts().into(),
+      Field::AuthApiStaticApiKeys => dict(
+        self
+          .get_auth_api_static_api_keys()
+          .into_iter()
+          .map(|(client, key)| (client, key.expose().into())),
+      ),
+      Field::AuthApiReplicas => self.get_auth_api_replicas().into(),
+      Field::AuthApiMaxSessionsPerUser => self.get_auth_api_max_sessions_per_user()?.into(),
+      Field::AuthApiMaxAccountsPerIpPerDay => {
+        self.get_auth_api_max_accounts_per_ip_per_day()?.into()
+      }
+      Field::AuthApiSignupEnabled => self.get_auth_api_signup_enabled().into(),
+      Field::AuthApiClockSkewToleranceSeconds => {
+        self.get_auth_api_clock_skew_tolerance_seconds().into()
+      }
+      Field::AuthApiPasswordMinLength => self.get_auth_api_password_min_length().into(),
+      Field::AuthApiPasswordRequireSymbols => self.get_auth_api_password_require_symbols().into(),
+      Field::AuthApiPasswordBreachCheckEnabled => {
+        self.get_auth_api_password_breach_check_enabled().into()
+      }
+      Field::AuthApiInviteOnly => self.get_auth_api_invite_only().into(),
+      Field::AuthApiInviteCodeTtlSeconds => self.get_auth_api_invite_code_ttl_seconds().into(),
+      Field::AuthApiUsernameMinLength => self.get_auth_api_username_min_length().into(),
+      Field::AuthApiUsernameMaxLength => self.get_auth_api_username_max_length().into(),
+      Field::AuthApiReservedUsernames => self.get_auth_api_reserved_usernames().into(),
+      Field::StoragePresignedUrlTtlSeconds => self.get_storage_presigned_url_ttl_seconds().into(),
+      Field::ImageproxyUrl => self.get_imageproxy_url().into(),
+      Field::ImageproxySigningKey => self.get_imageproxy_signing_key()?.into(),
+      Field::ImageproxySalt => self.get_imageproxy_salt()?.into(),
+      Field::ImageMaxDimensions => {
+        let (width, height) = self.get_image_max_dimensions();
+        vec![width, height].into()
+      }
+      Field::MediaMaxUploadBytes => self.get_media_max_upload_bytes().into(),
+      Field::MediaAllowedMimeTypes => self.get_media_allowed_mime_types().into(),
+      Field::MediaImageMaxPixels => self.get_media_image_max_pixels().into(),
+      Field::AssetVersion => self.get_asset_version()?.into(),
+      Field::AppVersion => self.get_app_version()?.into(),
+      Field::BuildSha => self.get_build_sha()?.into(),
+      Field::BuildTimestamp => self.get_build_timestamp()?.into(),
+      Field::AdminApiToken => self.get_admin_api_token()?.into(),
+      Field::InternalAuthSecret => self.get_internal_auth_secret()?.into(),
+      Field::InternalMtlsEnabled => self.get_internal_mtls_enabled().into(),
+      Field::InternalCaCertPath => path(self.get_internal_ca_cert_path()?),
+      Field::InternalClientCertPath => path(self.get_internal_client_cert_path()?),
+      Field::InternalClientKeyPath => path(self.get_internal_client_key_path()?),
+      Field::ContainerDnsMode => self.get_container_dns_mode().into(),
+      Field::ContainerServiceNameTemplate => self.get_container_service_name_template().into(),
+      Field::DiscoveryMode => self.get_discovery_mode().as_str().into(),
+      Field::AuthApiDiscoveredEndpoint => return None,
+      Field::LoadBalancerStrategy => self.get_load_balancer_strategy().as_str().into(),
+      Field::StickySessionsEnabled => self.get_sticky_sessions_enabled().into(),
+      Field::StickySessionCookieName => self.get_sticky_session_cookie_name().into(),
+      Field::WsHeartbeatIntervalSeconds => self.get_ws_heartbeat_interval_seconds().into(),
+      Field::WsClientTimeoutSeconds => self.get_ws_client_timeout_seconds().into(),
+      Field::WsMaxMessageSizeBytes => self.get_ws_max_message_size_bytes().into(),
+      Field::AcmeEnabled => self.get_acme_enabled().into(),
+      Field::AcmeContactEmail => self.get_acme_contact_email().into(),
+      Field::AcmeDirectoryUrl => self.get_acme_directory_url().into(),
+      Field::TraceSampleRatio => self.get_trace_sample_ratio().into(),
+      Field::MetricsEnabled => self.get_metrics_enabled().into(),
+      Field::LogSamplingRate => self.get_log_sampling_rate().into(),
+      Field::LogLevelOverrides => dict(
+        self
+          .get_log_level_overrides()
+          .into_iter()
+          .map(|(target, level)| (target, level.into())),
+      ),
+      Field::AuditLogEnabled => self.get_audit_log_enabled().into(),
+      Field::AuditLogSink => self.get_audit_log_sink().as_str().into(),
+      Field::AuditLogPath => storage(&self.audit_log_path, "audit.log"),
+      Field::AuditLogRetentionDays => self.get_audit_log_retention_days().into(),
+      Field::UserDataRetentionDays => self.get_user_data_retention_days().into(),
+      Field::DeletedAccountPurgeDelayDays => self.get_deleted_account_purge_delay_days().into(),
+      Field::BackupsEnabled => self.get_backups_enabled().into(),
+      Field::BackupEncryptionKey => self.get_backup_encryption_key()?.into(),
+      Field::RateLimitOverrides => dict(
+        self
+          .get_rate_limit_overrides()
+          .into_iter()
+          .map(|(route, limit)| (route, rate_limit(limit))),
+      ),
+      Field::OutboundRateLimits => dict(
+        self
+          .get_outbound_rate_limits()
+          .into_iter()
+          .map(|(integration, limit)| (integration, rate_limit(limit))),
+      ),
+      Field::FeatureFlags => dict(
+        self
+          .get_feature_flags()
+          .into_iter()
+          .map(|(name, flag)| (name, feature_flag(flag).into())),
+      ),
+      Field::ReadOnlyMode => self.get_read_only_mode().into(),
+      Field::DegradedSubsystems => self.get_degraded_subsystems().into(),
+      Field::WaitForDependencies => self.get_wait_for_dependencies().into(),
+      Field::DependencyWaitTimeoutSeconds => self.get_dependency_wait_timeout_seconds().into(),
+      Field::KeyVaultRetries => self.get_key_vault_retries().into(),
+      Field::KeyVaultTimeoutSeconds => self.get_key_vault_timeout_seconds().into(),
+      Field::DeclaredDependencies => dict(self.get_declared_dependencies().into_iter().map(
+        |(service, dependencies)| {
+          let names: Vec<String> = dependencies.iter().map(|d| d.to_string()).collect();
+          (service.to_string(), names.into())
+        },
+      )),
+      Field::ServiceUrls => dict(
+        self
+          .get_service_urls()
+          .into_iter()
+          .map(|(service, url)| (service, url.into())),
+      ),
+      Field::GameServerRegions => self.get_game_server_regions().into(),
+      Field::GameServerPortRange => {
+        let range = self.get_game_server_port_range();
+        format!("{}-{}", range.start(), range.end()).into()
+      }
+      Field::GameServerMaxPlayers => self.get_game_server_max_players().into(),
+      Field::MatchmakingSkillWindow => self.get_matchmaking_skill_window().into(),
+      Field::MatchmakingExpandIntervalSeconds => {
+        self.get_matchmaking_expand_interval_seconds().into()
+      }
+      Field::MatchmakingMaxWaitSeconds => self.get_matchmaking_max_wait_seconds().into(),
+      Field::LeaderboardApiSubdomain => self.get_leaderboard_api_subdomain().into(),
+      Field::LeaderboardApiPort => self.get_leaderboard_api_port()?.into(),
+      Field::LeaderboardApiProtocol => self.get_leaderboard_api_protocol().into(),
+      Field::LeaderboardCacheTtlSeconds => self.get_leaderboard_cache_ttl_seconds().into(),
+      Field::LeaderboardPageSize => self.get_leaderboard_page_size().into(),
+      Field::NotificationsApiSubdomain => self.get_notifications_api_subdomain().into(),
+      Field::NotificationsApiPort => self.get_notifications_api_port()?.into(),
+      Field::NotificationsApiProtocol => self.get_notifications_api_protocol().into(),
+      Field::NotificationsBatchSize => self.get_notifications_batch_size().into(),
+      Field::NotificationsRetryLimit => self.get_notifications_retry_limit().into(),
+      Field::TurnServerUrls => self.get_turn_server_urls().into(),
+      Field::TurnUsername => self.get_turn_username()?.into(),
+      Field::TurnCredential => self.get_turn_credential()?.into(),
+      Field::StunServerUrls => self.get_stun_server_urls().into(),
+      Field::TelemetryIngestSubdomain => self.get_telemetry_ingest_subdomain().into(),
+      Field::TelemetryIngestPort => self.get_telemetry_ingest_port()?.into(),
+      Field::TelemetryIngestProtocol => self.get_telemetry_ingest_protocol().into(),
+      Field::TelemetryIngestMaxBatchBytes => self.get_telemetry_ingest_max_batch_bytes().into(),
+      Field::TelemetryRetentionDays => self.get_telemetry_retention_days().into(),
+      Field::AnticheatApiUrl => self.get_anticheat_api_url()?.into(),
+      Field::AnticheatApiKey => self.get_anticheat_api_key()?.into(),
+      Field::AnticheatEnforcementMode => self.get_anticheat_enforcement_mode().as_str().into(),
+    };
+    Some(value)
+  }
+
+  /// Returns the effective value of every field, keyed like
+  /// [`DpsConfig::export`], leaving out fields that are not explicitly set
+  /// unless `defaults` is set. Values without a natural type keep their
+  /// variable format (`"2048x2048"`, `"10/min"`, RFC 3339 timestamps), and
+  /// secrets are masked unless `secrets` is set.
+  pub(crate) fn effective_values(
+    &self,
+    defaults: bool,
+    secrets: bool,
+  ) -> Vec<(String, ExportValue)> {
+    FIELDS
+      .iter()
+      .filter(|meta| defaults || self.is_explicitly_set(meta.field))
+      .filter_map(|meta| {
+        let value = self.effective_value(meta.field, secrets)?;
+        let key = meta.var.trim_start_matches("DPS_").trim_end_matches('_');
+        Some((key.to_ascii_lowercase(), value))
+      })
+      .collect()
+  }
+
+  /// Returns the effective value of `field`, as in
+  /// [`DpsConfig::effective_values`].
+  fn effective_value(&self, field: Field, secrets: bool) -> Option<ExportValue> {
+    let value = match field {
+      Field::ImageMaxDimensions => {
+        let (width, height) = self.get_image_max_dimensions();
+        format!("{width}x{height}").into()
+      }
+      Field::AuthApiSessionSecretExpiry => {
+        rfc3339::format(self.get_auth_api_session_secret_expiry()?).into()
+      }
+      Field::LogLevelOverrides => {
+        let mut overrides: Vec<String> = self
+          .get_log_level_overrides()
+          .into_iter()
+          .map(|(target, level)| format!("{target}={level}"))
+          .collect();
+        overrides.sort();
+        overrides.join(",").into()
+      }
+      Field::RateLimitOverrides => rate_limits(self.get_rate_limit_overrides()),
+      Field::OutboundRateLimits => rate_limits(self.get_outbound_rate_limits()),
+      Field::ServiceUrls if !secrets => dict(
+        self
+          .get_service_urls()
+          .into_iter()
+          .map(|(service, url)| (service, mask_password(&url).into())),
+      ),
+      field => self.export_value(field)?,
+    };
+    Some(match field.is_secret() && !secrets {
+      true => mask(value),
+      false => value,
+    })
+  }
+
+  /// Renders the config as `DPS_*=value` lines for an env file, so the
+  /// effective config of a running service can be captured and replayed
+  /// elsewhere with [`DpsConfig::load_env_file`]. Variables are named with
+  /// the prefix the config was read with ([`DpsConfig::env_var`]). Only explicitly set
+  /// values are written, unless `defaults` is set, in which case every
+  /// effective value is. Per-key variables get one line per key, and values
+  /// an env file would misread are double-quoted.
+  ///
+  /// Secrets are written as `# DPS_ADMIN_API_TOKEN=***` comments, so they
+  /// show as set but have to be supplied separately. Service URLs holding a
+  /// password are commented out the same way, with the password masked.
+  ///
+  /// ```rust
+  /// use dps_config::DpsConfig;
+  ///
+  /// let config = DpsConfig::from_map([
+  ///   ("DPS_DOMAIN", "example.com"),
+  ///   ("DPS_RATE_LIMIT_LOGIN", "10/min"),
+  ///   ("DPS_ADMIN_API_TOKEN", "t0ken"),
+  /// ]);
+  /// assert_eq!(
+  ///   config.to_env_string(false),
+  ///   "DPS_DOMAIN=example.com\n# DPS_ADMIN_API_TOKEN=***\nDPS_RATE_LIMIT_LOGIN=10/min\n"
+  /// );
+  /// ```
+  pub fn to_env_string(&self, defaults: bool) -> String {
+    let mut out = String::new();
+    for meta in FIELDS {
+      if !defaults && !self.is_explicitly_set(meta.field) {
+        continue;
+      }
+      let Some(value) = self.effective_value(meta.field, false) else {
+        continue;
+      };
+      let comment = if meta.field.is_secret() { "# " } else { "" };
+      let var = self.env_var(meta.var);
+      match value {
+        ExportValue::Table(entries) => {
+          for (key, value) in entries {
+            // Service URLs with a masked password are written like secrets
+            let masked = meta.field == Field::ServiceUrls
+              && (self.service_urls.get(&key)).is_some_and(|url| mask_password(url) != *url);
+            let comment = if masked { "# " } else { comment };
+            let key = key.to_ascii_uppercase();
+            let value = env_value(&value);
+            out.push_str(&format!("{comment}{var}{key}={value}\n"));
+          }
+        }
+        value => out.push_str(&format!("{comment}{var}={}\n", env_value(&value))),
+      }
+    }
+    out
+  }
+
+  /// Renders the fully resolved config, defaults included, as a TOML
+  /// document with the key layout of [`DpsConfig::from_toml_file`], e.g. for
+  /// support to see exactly what a node thinks its config is. Per-key
+  /// variables become tables after the top-level keys, lists are
+  /// comma-separated strings and secrets, as well as passwords in service
+  /// URLs, are masked as `"***"`. Loading the output back sets secrets to
+  /// that literal `"***"`, so they have to be
+  /// supplied again, e.g. from the environment.
+  ///
+  /// ```rust
+  /// use dps_config::DpsConfig;
+  ///
+  /// let config = DpsConfig::from_map([
+  ///   ("DPS_DOMAIN", "example.com"),
+  ///   ("DPS_RATE_LIMIT_LOGIN", "10/min"),
+  /// ]);
+  /// let toml = config.to_toml();
+  /// assert!(toml.starts_with("domain = \"example.com\"\napi_path = \"api\"\n"));
+  /// assert!(toml.ends_with("\n[rate_limit]\nlogin = \"10/min\"\n"));
+  /// ```
+  pub fn to_toml(&self) -> String {
+    let mut out = String::new();
+    let mut tables = String::new();
+    for (key, value) in self.effective_values(true, false) {
+      match value {
+        ExportValue::Table(entries) if entries.is_empty() => {}
+        ExportValue::Table(entries) => {
+          tables.push_str(&format!("\n[{key}]\n"));
+          for (key, value) in entries {
+            tables.push_str(&format!("{key} = {}\n", document_value(&value)));
+          }
+        }
+        value => out.push_str(&format!("{key} = {}\n", document_value(&value))),
+      }
+    }
+    out + &tables
+  }
+
+  /// Like [`DpsConfig::to_toml`], but renders a JSON document with the key
+  /// layout of [`DpsConfig::from_json_str`], indented by two spaces.
+  ///
+  /// ```rust
+  /// use dps_config::DpsConfig;
+  ///
+  /// let config = DpsConfig::from_map([("DPS_ADMIN_API_TOKEN", "t0ken")]);
+  /// let json = config.to_json();
+  /// assert!(json.starts_with("{\n  \"domain\": \"dps.localhost\",\n"));
+  /// assert!(json.contains("\n  \"admin_api_token\": \"***\",\n"));
+  /// ```
+  pub fn to_json(&self) -> String {
+    let entries: Vec<String> = self
+      .effective_values(true, false)
+      .into_iter()
+      .map(|(key, value)| {
+        let value = match value {
+          ExportValue::Table(entries) if entries.is_empty() => "{}".to_string(),
+          ExportValue::Table(entries) => {
+            let entries: Vec<String> = entries
+              .iter()
+              .map(|(key, value)| {
+                format!("    {}: {}", crate::json_string(key), document_value(value))
+              })
+              .collect();
+            format!("{{\n{}\n  }}", entries.join(",\n"))
+          }
+          value => document_value(&value),
+        };
+        format!("  {}: {value}", crate::json_string(&key))
+      })
+      .collect();
+    format!("{{\n{}\n}}\n", entries.join(",\n"))
+  }
+}
+
+/// Formats `value` as a TOML or JSON value, with lists as comma-separated
+/// strings as the config file readers expect.
+fn document_value(value: &ExportValue) -> String {
+  match value {
+    ExportValue::Bool(value) => value.to_string(),
+    ExportValue::Number(value) => value.to_string(),
+    // NaN and infinity have no TOML or JSON number form
+    ExportValue::Float(value) if !value.is_finite() => crate::json_string(&value.to_string()),
+    ExportValue::Float(value) => value.to_string(),
+    value => crate::json_string(&env_text(value)),
+  }
+}
+
+/// Formats `value` as an env file value, quoting it when needed.
+fn env_value(value: &ExportValue) -> String {
+  let value = env_text(value);
+  let plain =
+    value.trim() == value && !value.starts_with(['"', '\'']) && !value.contains(['#', '\n']);
+  match plain {
+    true => value,
+    false => format!(
+      "\"{}\"",
+      value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+    ),
+  }
+}
+
+/// Formats `value` as its environment variable would hold it.
+fn env_text(value: &ExportValue) -> String {
+  match value {
+    ExportValue::Bool(value) => if *value { "Y" } else { "N" }.to_string(),
+    ExportValue::Number(value) => value.to_string(),
+    ExportValue::Float(value) => value.to_string(),
+    ExportValue::String(value) => value.clone(),
+    ExportValue::List(values) => {
+      let values: Vec<String> = values.iter().map(env_text).collect();
+      values.join(",")
+    }
+    ExportValue::Table(_) => unreachable!("tables are written one line per key"),
+  }
+}
+
+/// Prints the effective value of every field, defaults included, with
+/// secrets ([`Field::is_secret`]) and passwords in service URLs replaced by
+/// `"***"` so the config can be logged at startup or attached to error
+/// context.
+impl fmt::Debug for DpsConfig {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let values = self.effective_values(true, false);
+    let mut debug = f.debug_struct("DpsConfig");
+    for (key, value) in &values {
+      debug.field(key, &DebugValue(value));
+    }
+    debug.finish()
+  }
+}
+
+/// Formats an [`ExportValue`] without its variant names.
+struct DebugValue<'a>(&'a ExportValue);
+
+impl fmt::Debug for DebugValue<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.0 {
+      ExportValue::Bool(value) => fmt::Debug::fmt(value, f),
+      ExportValue::Number(value) => fmt::Debug::fmt(value, f),
+      ExportValue::Float(value) => fmt::Debug::fmt(value, f),
+      ExportValue::String(value) => fmt::Debug::fmt(value, f),
+      ExportValue::List(values) => f
+        .debug_list()
+        .entries(values.iter().map(DebugValue))
+        .finish(),
+      ExportValue::Table(entries) => f
+        .debug_map()
+        .entries(entries.iter().map(|(key, value)| (key, DebugValue(value))))
+        .finish(),
+    }
+  }
+}
+
+fn dict(entries: impl Iterator<Item = (String, ExportValue)>) -> ExportValue {
+  let mut entries: Vec<_> = entries.collect();
+  entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+  ExportValue::Table(entries)
+}
+
+/// Returns rate limits as a table of `COUNT/UNIT` strings.
+fn rate_limits(limits: HashMap<String, RateLimit>) -> ExportValue {
+  dict(
+    limits
+      .into_iter()
+      .map(|(key, limit)| (key, limit.to_string().into())),
+  )
+}
+
+/// Masks a secret, keeping the keys of per-key secrets such as API keys.
+fn mask(value: ExportValue) -> ExportValue {
+  match value {
+    ExportValue::Table(entries) => ExportValue::Table(
+      entries
+        .into_iter()
+        .map(|(key, _)| (key, MASK.into()))
+        .collect(),
+    ),
+    _ => MASK.into(),
+  }
+}
+
+/// Masks the password in the userinfo of `url`, as in
+/// `postgres://dps:***@db/dps`.
+fn mask_password(url: &str) -> String {
+  let Some((scheme, rest)) = url.split_once("://") else {
+    return url.to_string();
+  };
+  let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+  let Some((userinfo, host)) = authority.rsplit_once('@') else {
+    return url.to_string();
+  };
+  let Some((user, _)) = userinfo.split_once(':') else {
+    return url.to_string();
+  };
+  let path = &rest[authority.len()..];
+  format!("{scheme}://{user}:{MASK}@{host}{path}")
+}
+
+/// Returns `limit` as a `{count, per_seconds}` table.
+fn rate_limit(limit: RateLimit) -> ExportValue {
+  dict(
+    [
+      ("count".to_string(), limit.count.into()),
+      ("per_seconds".to_string(), limit.per.as_secs().into()),
+    ]
+    .into_iter(),
+  )
+}
+
+fn feature_flag(flag: FeatureFlag) -> String {
+  match flag {
+    FeatureFlag::Enabled => "Y".to_string(),
+    FeatureFlag::Disabled => "N".to_string(),
+    FeatureFlag::Rollout(percent) => format!("{percent}%"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_debug() {
+    let config = DpsConfig::from_map([
+      ("DPS_DOMAIN", "example.com"),
+      ("DPS_AUTH_API_PORT", "3000"),
+      ("DPS_AUTH_API_SESSION_SECRET", "s3cret"),
+      ("DPS_AUTH_API_KEY_BILLING", "key-1"),
+      ("DPS_ADMIN_API_TOKEN", "t0ken"),
+      ("DPS_IMAGE_MAX_DIMENSIONS", "1920x1080"),
+      ("DPS_SERVICE_URL_POSTGRES", "postgres://dps:hunter2@db/dps"),
+      ("DPS_SERVICE_URL_REDIS", "redis://redis:6379/0"),
+    ]);
+    let debug = format!("{config:?}");
+    assert!(debug.starts_with("DpsConfig { domain: \"example.com\", "));
+    assert!(debug.contains("auth_api_port: 3000, "));
+    assert!(debug.contains("auth_api_session_secret: \"***\", "));
+    assert!(debug.contains("auth_api_key: {\"billing\": \"***\"}, "));
+    assert!(debug.contains("admin_api_token: \"***\""));
+    assert!(debug.contains("image_max_dimensions: \"1920x1080\", "));
+    assert!(debug.contains(
+      "service_url: {\"postgres\": \"postgres://dps:***@db/dps\", \"redis\": \"redis://redis:6379/0\"}"
+    ));
+    // Defaults are included
+    assert!(debug.contains("api_path: \"api\", "));
+    for secret in ["s3cret", "key-1", "t0ken", "hunter2"] {
+      assert!(!debug.contains(secret), "{secret}");
+    }
+    assert!(format!("{config:#?}").contains("\n    domain: \"example.com\",\n"));
+
+    for (url, masked) in [
+      (
+        "postgres://dps:hunter2@db:5432/dps",
+        "postgres://dps:***@db:5432/dps",
+      ),
+      ("redis://:hunter2@redis", "redis://:***@redis"),
+      ("amqp://guest@rabbit", "amqp://guest@rabbit"),
+      ("http://game-api:3000/a@b", "http://game-api:3000/a@b"),
+      ("not a url", "not a url"),
+    ] {
+      assert_eq!(mask_password(url), masked);
+    }
+  }
+
+  #[test]
+  fn test_to_toml_and_json() {
+    let config = DpsConfig::from_map([
+      ("DPS_DOMAIN", "example.com"),
+      ("DPS_AUTH_API_PORT", "3000"),
+      ("DPS_AUTH_API_SESSION_SECRET", "s3cret"),
+      (
+        "DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS",
+        "a.example.com, b.example.com",
+      ),
+      ("DPS_TRACE_SAMPLE_RATIO", "0.25"),
+      ("DPS_MOTD", "ignored"),
+      ("DPS_RATE_LIMIT_LOGIN", "10/min"),
+      ("DPS_FEATURE_NEW_EDITOR", "Y"),
+      ("DPS_SERVICE_URL_POSTGRES", "postgres://dps:hunter2@db/dps"),
+    ]);
+    let check = |loaded: DpsConfig| {
+      assert_eq!(loaded.get_domain(), "example.com");
+      assert_eq!(loaded.get_auth_api_port(), Some(3000));
+      assert_eq!(loaded.get_auth_api_session_secret().as_deref(), Some("***"));
+      assert_eq!(
+        loaded.get_auth_api_allowed_redirect_hosts(),
+        config.get_auth_api_allowed_redirect_hosts()
+      );
+      assert_eq!(loaded.get_trace_sample_ratio(), 0.25);
+      assert_eq!(
+        loaded.get_rate_limit_overrides(),
+        config.get_rate_limit_overrides()
+      );
+      assert_eq!(loaded.get_feature_flags(), config.get_feature_flags());
+      assert_eq!(loaded.get_image_max_dimensions(), (2048, 2048));
+    };
+
+    let toml = config.to_toml();
+    assert!(toml.contains("\nauth_api_session_secret = \"***\"\n"));
+    assert!(toml.contains("\ntrace_sample_ratio = 0.25\n"));
+    assert!(toml.contains("\n[feature]\nnew_editor = \"Y\"\n"));
+    assert!(toml.contains("\n[service_url]\npostgres = \"postgres://dps:***@db/dps\"\n"));
+    assert!(!toml.contains("s3cret"));
+    assert!(!toml.contains("hunter2"));
+    let vars = crate::toml::vars(&toml).unwrap();
+    check(DpsConfig::load(&mut crate::EnvLoader::from_vars(vars)));
+
+    let json = config.to_json();
+    assert!(json.contains("\n  \"rate_limit\": {\n    \"login\": \"10/min\"\n  },\n"));
+    assert!(json.contains("\n  \"log_level_overrides\": \"\",\n"));
+    assert!(json.contains("\n    \"postgres\": \"postgres://dps:***@db/dps\"\n"));
+    assert!(!json.contains("s3cret"));
+    assert!(!json.contains("hunter2"));
+    check(DpsConfig::from_json_str(&json).unwrap());
+
+    // Non-finite numbers set in code render as strings, read back as unset
+    let mut config = DpsConfig::from_map::<_, &str, &str>([]);
+    config.set_trace_sample_ratio(Some(f64::NAN));
+    config.set_log_sampling_rate(Some(f64::INFINITY));
+    let toml = config.to_toml();
+    assert!(toml.contains("\ntrace_sample_ratio = \"NaN\"\n"));
+    assert!(toml.contains("\nlog_sampling_rate = \"inf\"\n"));
+    let loaded = DpsConfig::from_json_str(&config.to_json()).unwrap();
+    assert!(!loaded.is_explicitly_set(Field::TraceSampleRatio));
+    assert!(loaded.validate().is_err());
+  }
+
+  #[test]
+  fn test_to_env_string() {
+    let config = DpsConfig::from_map([
+      ("DPS_DOMAIN", "example.com"),
+      ("DPS_DEVELOPMENT_MODE", "Y"),
+      ("DPS_AUTH_API_SESSION_SECRET", "s3cret"),
+      ("DPS_AUTH_API_KEY_BILLING", "key-1"),
+      (
+        "DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS",
+        "a.example.com, b.example.com",
+      ),
+      ("DPS_ACME_CONTACT_EMAIL", " ops #1 "),
+      ("DPS_LOG_LEVEL_OVERRIDES", "sqlx=warn,tower_http=debug"),
+      ("DPS_FEATURE_NEW_EDITOR", "25%"),
+      ("DPS_SERVICE_URL_POSTGRES", "postgres://dps:hunter2@db/dps"),
+    ]);
+    assert_eq!(
+      config.to_env_string(false),
+      "DPS_DOMAIN=example.com\n\
+       DPS_DEVELOPMENT_MODE=Y\n\
+       # DPS_AUTH_API_SESSION_SECRET=***\n\
+       DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS=a.example.com,b.example.com\n\
+       # DPS_AUTH_API_KEY_BILLING=***\n\
+       DPS_ACME_CONTACT_EMAIL=\" ops #1 \"\n\
+       DPS_LOG_LEVEL_OVERRIDES=sqlx=warn,tower_http=debug\n\
+       DPS_FEATURE_NEW_EDITOR=25%\n\
+       # DPS_SERVICE_URL_POSTGRES=postgres://dps:***@db/dps\n"
+    );
+
+    let defaults = config.to_env_string(true);
+    assert!(defaults.contains("\nDPS_API_PATH=api\n"));
+    let replayed = DpsConfig::from_map(
+      crate::env_file::vars(&defaults)
+        .into_iter()
+        .map(|(k, v)| (k.into_string().unwrap(), v.into_string().unwrap())),
+    );
+    // Secrets are left out of the replay
+    let without_secrets: String = defaults
+      .lines()
+      .filter(|line| !line.starts_with('#'))
+      .map(|line| format!("{line}\n"))
+      .collect();
+    assert_eq!(replayed.to_env_string(true), without_secrets);
+    assert_eq!(replayed.get_audit_log_path(), config.get_audit_log_path());
+    assert_eq!(replayed.get_acme_contact_email(), " ops #1 ");
+    assert_eq!(replayed.get_auth_api_session_secret(), None);
+  }
+
+  #[test]
+  fn test_to_env_string_with_prefix() {
+    let load = |vars: crate::env_snapshot::EnvVars| {
+      DpsConfig::load(&mut crate::EnvLoader::with_prefix(
+        vars,
+        Some("STAGING_DPS"),
+      ))
+    };
+    let config = load(
+      [
+        ("STAGING_DPS_DOMAIN", "staging.example.com"),
+        ("STAGING_DPS_ADMIN_API_TOKEN", "t0ken"),
+        ("STAGING_DPS_RATE_LIMIT_LOGIN", "10/min"),
+      ]
+      .into_iter()
+      .map(|(k, v)| (k.into(), v.into()))
+      .collect(),
+    );
+    let env = config.to_env_string(false);
+    assert_eq!(
+      env,
+      "STAGING_DPS_DOMAIN=staging.example.com\n\
+       # STAGING_DPS_ADMIN_API_TOKEN=***\n\
+       STAGING_DPS_RATE_LIMIT_LOGIN=10/min\n"
+    );
+
+    let replayed = load(crate::env_file::vars(&env));
+    assert_eq!(replayed.get_domain(), "staging.example.com");
+    assert_eq!(
+      replayed.get_rate_limit("login"),
+      config.get_rate_limit("login")
+    );
+    assert_eq!(
+      replayed.to_env_string(true),
+      config
+        .to_env_string(true)
+        .replace("# STAGING_DPS_ADMIN_API_TOKEN=***\n", "")
+    );
+  }
+}
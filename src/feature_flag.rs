@@ -0,0 +1,86 @@
+//! Feature flags parsed from `Y`, `N` or percentage rollout strings.
+
+use crate::hash;
+
+/// State of a feature flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeatureFlag {
+  Enabled,
+  Disabled,
+  /// Enabled for the given percentage (0 to 100) of stable ids.
+  Rollout(u8),
+}
+
+impl FeatureFlag {
+  /// Parses `"Y"`, `"N"` or a percentage such as `"25%"` (0 to 100).
+  /// Returns `None` for anything else.
+  pub fn parse(value: &str) -> Option<FeatureFlag> {
+    match value.trim() {
+      "Y" => Some(FeatureFlag::Enabled),
+      "N" => Some(FeatureFlag::Disabled),
+      value => {
+        let percent: u8 = value.strip_suffix('%')?.trim().parse().ok()?;
+        (percent <= 100).then_some(FeatureFlag::Rollout(percent))
+      }
+    }
+  }
+
+  /// Returns whether the flag is on for `stable_id` (e.g. a user id).
+  /// Rollouts hash the feature name and id with SHA-256 into one of 100
+  /// buckets, so an id keeps its answer across processes and releases, and
+  /// raising the percentage only ever adds ids.
+  pub fn is_enabled_for(&self, feature: &str, stable_id: &str) -> bool {
+    match *self {
+      FeatureFlag::Enabled => true,
+      FeatureFlag::Disabled => false,
+      FeatureFlag::Rollout(percent) => bucket(feature, stable_id) < percent,
+    }
+  }
+}
+
+fn bucket(feature: &str, stable_id: &str) -> u8 {
+  let digest = hash::sha256(format!("{feature}:{stable_id}").as_bytes());
+  let mut head = [0u8; 8];
+  head.copy_from_slice(&digest[..8]);
+  (u64::from_be_bytes(head) % 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(FeatureFlag::parse("Y"), Some(FeatureFlag::Enabled));
+    assert_eq!(FeatureFlag::parse("N"), Some(FeatureFlag::Disabled));
+    assert_eq!(FeatureFlag::parse("25%"), Some(FeatureFlag::Rollout(25)));
+    assert_eq!(FeatureFlag::parse(" 0 %"), Some(FeatureFlag::Rollout(0)));
+    for invalid in ["", "yes", "25", "101%", "-1%", "2.5%"] {
+      assert!(FeatureFlag::parse(invalid).is_none(), "{invalid}");
+    }
+  }
+
+  #[test]
+  fn test_is_enabled_for() {
+    let ids: Vec<String> = (0..1000).map(|i| format!("user-{i}")).collect();
+    let enabled = |flag: FeatureFlag| {
+      ids
+        .iter()
+        .filter(|id| flag.is_enabled_for("new_login", id))
+        .count()
+    };
+    assert_eq!(enabled(FeatureFlag::Enabled), 1000);
+    assert_eq!(enabled(FeatureFlag::Disabled), 0);
+    assert_eq!(enabled(FeatureFlag::Rollout(0)), 0);
+    assert_eq!(enabled(FeatureFlag::Rollout(100)), 1000);
+    let quarter = enabled(FeatureFlag::Rollout(25));
+    assert!((200..300).contains(&quarter), "{quarter}");
+
+    // Raising the percentage keeps every id that was already enabled
+    for id in &ids {
+      if FeatureFlag::Rollout(25).is_enabled_for("new_login", id) {
+        assert!(FeatureFlag::Rollout(50).is_enabled_for("new_login", id));
+      }
+    }
+  }
+}
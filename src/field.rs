@@ -0,0 +1,1210 @@
+//! Names and documentation of the configurable properties of
+//! [`DpsConfig`](crate::DpsConfig).
+
+/// Identifies a property of [`DpsConfig`](crate::DpsConfig), e.g. for
+/// [`DpsConfig::is_explicitly_set`](crate::DpsConfig::is_explicitly_set).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Field {
+  Domain,
+  ApiPath,
+  EnvironmentSubdomainSuffix,
+  DevelopmentMode,
+  Profile,
+  DevLocalhostUrls,
+  DevTlsAutogenerate,
+  DevTlsCertDir,
+  SecretExpiryWarningDays,
+  RespectPlatformPort,
+  DataDir,
+  AuthApiSubdomain,
+  AuthApiPort,
+  AuthApiProtocol,
+  AuthApiInsecureCookie,
+  AuthApiSqliteMainFilePath,
+  AuthApiSqliteMainPoolSize,
+  AuthApiSessionSecret,
+  AuthApiSessionSecretExpiry,
+  AuthApiSessionSigningAlgorithm,
+  AuthApiSessionTtlSeconds,
+  AuthApiSessionIdleTtlSeconds,
+  AuthApiRememberMeTtlSeconds,
+  AuthApiSessionCleanupIntervalSeconds,
+  AuthApiSessionCleanupBatchSize,
+  AuthApiAllowedRedirectHosts,
+  AuthApiStaticApiKeys,
+  AuthApiReplicas,
+  AuthApiMaxSessionsPerUser,
+  AuthApiMaxAccountsPerIpPerDay,
+  AuthApiSignupEnabled,
+  AuthApiClockSkewToleranceSeconds,
+  AuthApiPasswordMinLength,
+  AuthApiPasswordRequireSymbols,
+  AuthApiPasswordBreachCheckEnabled,
+  AuthApiInviteOnly,
+  AuthApiInviteCodeTtlSeconds,
+  AuthApiUsernameMinLength,
+  AuthApiUsernameMaxLength,
+  AuthApiReservedUsernames,
+  StoragePresignedUrlTtlSeconds,
+  ImageproxyUrl,
+  ImageproxySigningKey,
+  ImageproxySalt,
+  ImageMaxDimensions,
+  MediaMaxUploadBytes,
+  MediaAllowedMimeTypes,
+  MediaImageMaxPixels,
+  AssetVersion,
+  AppVersion,
+  BuildSha,
+  BuildTimestamp,
+  AdminApiToken,
+  InternalAuthSecret,
+  InternalMtlsEnabled,
+  InternalCaCertPath,
+  InternalClientCertPath,
+  InternalClientKeyPath,
+  ContainerDnsMode,
+  ContainerServiceNameTemplate,
+  DiscoveryMode,
+  AuthApiDiscoveredEndpoint,
+  LoadBalancerStrategy,
+  StickySessionsEnabled,
+  StickySessionCookieName,
+  WsHeartbeatIntervalSeconds,
+  WsClientTimeoutSeconds,
+  WsMaxMessageSizeBytes,
+  AcmeEnabled,
+  AcmeContactEmail,
+  AcmeDirectoryUrl,
+  TraceSampleRatio,
+  MetricsEnabled,
+  LogSamplingRate,
+  LogLevelOverrides,
+  AuditLogEnabled,
+  AuditLogSink,
+  AuditLogPath,
+  AuditLogRetentionDays,
+  UserDataRetentionDays,
+  DeletedAccountPurgeDelayDays,
+  BackupsEnabled,
+  BackupEncryptionKey,
+  RateLimitOverrides,
+  OutboundRateLimits,
+  FeatureFlags,
+  ReadOnlyMode,
+  DegradedSubsystems,
+  WaitForDependencies,
+  DependencyWaitTimeoutSeconds,
+  DeclaredDependencies,
+  ServiceUrls,
+  KeyVaultRetries,
+  KeyVaultTimeoutSeconds,
+  GameServerRegions,
+  GameServerPortRange,
+  GameServerMaxPlayers,
+  MatchmakingSkillWindow,
+  MatchmakingExpandIntervalSeconds,
+  MatchmakingMaxWaitSeconds,
+  LeaderboardApiSubdomain,
+  LeaderboardApiPort,
+  LeaderboardApiProtocol,
+  LeaderboardCacheTtlSeconds,
+  LeaderboardPageSize,
+  NotificationsApiSubdomain,
+  NotificationsApiPort,
+  NotificationsApiProtocol,
+  NotificationsBatchSize,
+  NotificationsRetryLimit,
+  TurnServerUrls,
+  TurnUsername,
+  TurnCredential,
+  StunServerUrls,
+  TelemetryIngestSubdomain,
+  TelemetryIngestPort,
+  TelemetryIngestProtocol,
+  TelemetryIngestMaxBatchBytes,
+  TelemetryRetentionDays,
+  AnticheatApiUrl,
+  AnticheatApiKey,
+  AnticheatEnforcementMode,
+}
+
+impl Field {
+  /// Returns whether the property holds secrets, which are masked when the
+  /// config is serialized or printed and can also be read from `_FILE`
+  /// variables and systemd credentials.
+  pub fn is_secret(self) -> bool {
+    matches!(
+      self,
+      Field::AuthApiSessionSecret
+        | Field::AuthApiStaticApiKeys
+        | Field::ImageproxySigningKey
+        | Field::ImageproxySalt
+        | Field::AdminApiToken
+        | Field::InternalAuthSecret
+        | Field::BackupEncryptionKey
+        | Field::TurnCredential
+        | Field::AnticheatApiKey
+    )
+  }
+}
+
+/// A group of related properties, as listed in the README.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Section {
+  Global,
+  AuthApi,
+  Storage,
+  ImageProcessing,
+  Media,
+  StaticAssets,
+  BuildInfo,
+  AdminApi,
+  InternalServices,
+  RealtimeGateway,
+  Acme,
+  Observability,
+  AuditLog,
+  DataRetention,
+  Backups,
+  RateLimiting,
+  FeatureFlags,
+  IncidentResponse,
+  Startup,
+  GameServers,
+  Matchmaking,
+  LeaderboardApi,
+  NotificationsApi,
+  VoiceRtc,
+  TelemetryIngest,
+  Anticheat,
+}
+
+/// Documentation for the environment variable backing a property, returned by
+/// [`DpsConfig::describe`](crate::DpsConfig::describe).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldMeta {
+  pub field: Field,
+  pub section: Section,
+  /// The environment variable, or its prefix for per-key variables such as
+  /// `DPS_RATE_LIMIT_<ROUTE>`.
+  pub var: &'static str,
+  pub description: &'static str,
+  /// The accepted format of the variable's value.
+  pub format: &'static str,
+  /// The value used when the variable is unset, if there is a fixed one.
+  pub default: Option<&'static str>,
+}
+
+pub(crate) const FIELDS: &[FieldMeta] = &[
+  FieldMeta {
+    field: Field::Domain,
+    section: Section::Global,
+    var: "DPS_DOMAIN",
+    description: "Main domain of the website",
+    format: "text",
+    default: Some("dps.localhost"),
+  },
+  FieldMeta {
+    field: Field::ApiPath,
+    section: Section::Global,
+    var: "DPS_API_PATH",
+    description: "Path (without leading slash) for API endpoints",
+    format: "text",
+    default: Some("api"),
+  },
+  FieldMeta {
+    field: Field::EnvironmentSubdomainSuffix,
+    section: Section::Global,
+    var: "DPS_ENVIRONMENT_SUBDOMAIN_SUFFIX",
+    description: "Environment label inserted before the domain in computed hosts (e.g. staging)",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::DevelopmentMode,
+    section: Section::Global,
+    var: "DPS_DEVELOPMENT_MODE",
+    description: "Enables development-only features",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::Profile,
+    section: Section::Global,
+    var: "DPS_PROFILE",
+    description: "Deployment profile selecting profile-specific defaults and dps.{profile}.toml",
+    format: "\"development\", \"test\" or \"production\"",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::DevLocalhostUrls,
+    section: Section::Global,
+    var: "DPS_DEV_LOCALHOST_URLS",
+    description: "With development_mode, computed service URLs use http://localhost:{port} instead of subdomains (no local DNS needed)",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::DevTlsAutogenerate,
+    section: Section::Global,
+    var: "DPS_DEV_TLS_AUTOGENERATE",
+    description: "With development_mode, generate a self-signed certificate for *.{domain} (requires the dev-tls feature)",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::DevTlsCertDir,
+    section: Section::Global,
+    var: "DPS_DEV_TLS_CERT_DIR",
+    description: "Directory where development certificates are cached",
+    format: "file system path",
+    default: Some("{data_dir}/dev-tls"),
+  },
+  FieldMeta {
+    field: Field::SecretExpiryWarningDays,
+    section: Section::Global,
+    var: "DPS_SECRET_EXPIRY_WARNING_DAYS",
+    description: "How many days before a secret's expiry warnings() reports it",
+    format: "non-negative whole number",
+    default: Some("14"),
+  },
+  FieldMeta {
+    field: Field::RespectPlatformPort,
+    section: Section::Global,
+    var: "DPS_RESPECT_PLATFORM_PORT",
+    description: "Use the platform-standard PORT variable (Heroku, Cloud Run) for auth_api_port when DPS_AUTH_API_PORT is unset",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::DataDir,
+    section: Section::Global,
+    var: "DPS_DATA_DIR",
+    description: "Directory holding local state; default storage paths live inside it and relative ones resolve against it when set",
+    format: "file system path",
+    default: Some("data"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSubdomain,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SUBDOMAIN",
+    description: "Sub-subdomain for DpsAuthApi",
+    format: "text",
+    default: Some("auth"),
+  },
+  FieldMeta {
+    field: Field::AuthApiPort,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_PORT",
+    description: "Port for DpsAuthApi (omitted from URL if unset)",
+    format: "whole number from 0 to 65535",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuthApiProtocol,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_PROTOCOL",
+    description: "Protocol for DpsAuthApi",
+    format: "\"http\" or \"https\"",
+    default: Some("https"),
+  },
+  FieldMeta {
+    field: Field::AuthApiInsecureCookie,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_INSECURE_COOKIE",
+    description: "Allow insecure cookies (HTTP)",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSqliteMainFilePath,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SQLITE_MAIN_FILE_PATH",
+    description: "SQLite main database file path",
+    format: "file system path",
+    default: Some("{data_dir}/main-development.db"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSqliteMainPoolSize,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SQLITE_MAIN_POOL_SIZE",
+    description: "SQLite main database connection pool size",
+    format: "whole number from 0 to 65535",
+    default: Some("1"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionSecret,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_SECRET",
+    description: "32-byte session secret for encryption",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionSecretExpiry,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_SECRET_EXPIRES_AT",
+    description: "When the session secret expires",
+    format: "RFC 3339 timestamp",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionSigningAlgorithm,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_SIGNING_ALGORITHM",
+    description: "Algorithm used to sign session cookies with the session secret",
+    format: "\"hmac-sha256\", \"hmac-sha512\" or \"ed25519\"",
+    default: Some("hmac-sha256"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionTtlSeconds,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_TTL_SECONDS",
+    description: "Session TTL in seconds",
+    format: "non-negative whole number",
+    default: Some("1209600"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionIdleTtlSeconds,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_IDLE_TTL_SECONDS",
+    description: "How long an unused session stays valid, at most the session TTL",
+    format: "non-negative whole number",
+    default: Some("{auth_api_session_ttl_seconds}"),
+  },
+  FieldMeta {
+    field: Field::AuthApiRememberMeTtlSeconds,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_REMEMBER_ME_TTL_SECONDS",
+    description: "Session TTL in seconds for users who opt into long-lived sessions",
+    format: "non-negative whole number",
+    default: Some("2592000"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionCleanupIntervalSeconds,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_CLEANUP_INTERVAL_SECONDS",
+    description: "How often the expired-session sweeper runs, in seconds",
+    format: "positive whole number",
+    default: Some("3600"),
+  },
+  FieldMeta {
+    field: Field::AuthApiSessionCleanupBatchSize,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SESSION_CLEANUP_BATCH_SIZE",
+    description: "Maximum number of expired sessions deleted per sweeper batch",
+    format: "positive whole number",
+    default: Some("1000"),
+  },
+  FieldMeta {
+    field: Field::AuthApiAllowedRedirectHosts,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS",
+    description: "Hosts that login redirects may point to",
+    format: "comma-separated hosts; a \"*.\" prefix also matches subdomains",
+    default: Some("{domain},*.{domain}"),
+  },
+  FieldMeta {
+    field: Field::AuthApiStaticApiKeys,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_KEY_",
+    description: "Static API key of a machine client, keyed by the lowercase variable suffix",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuthApiReplicas,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_REPLICAS",
+    description: "Number of Auth API replicas callers balance across",
+    format: "whole number from 1 to 65535",
+    default: Some("1"),
+  },
+  FieldMeta {
+    field: Field::AuthApiMaxSessionsPerUser,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_MAX_SESSIONS_PER_USER",
+    description: "Maximum number of concurrent sessions per user",
+    format: "positive whole number",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuthApiMaxAccountsPerIpPerDay,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_MAX_ACCOUNTS_PER_IP_PER_DAY",
+    description: "Maximum number of accounts created from one IP address per day",
+    format: "positive whole number",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuthApiSignupEnabled,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_SIGNUP_ENABLED",
+    description: "Allow new users to sign up",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("true"),
+  },
+  FieldMeta {
+    field: Field::AuthApiClockSkewToleranceSeconds,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_CLOCK_SKEW_TOLERANCE_SECONDS",
+    description: "Clock drift tolerated when checking token and session expiry",
+    format: "non-negative whole number",
+    default: Some("60"),
+  },
+  FieldMeta {
+    field: Field::AuthApiPasswordMinLength,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_PASSWORD_MIN_LENGTH",
+    description: "Minimum password length",
+    format: "positive whole number",
+    default: Some("12"),
+  },
+  FieldMeta {
+    field: Field::AuthApiPasswordRequireSymbols,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_PASSWORD_REQUIRE_SYMBOLS",
+    description: "Require a symbol (neither letter nor digit) in passwords",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::AuthApiPasswordBreachCheckEnabled,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_PASSWORD_BREACH_CHECK_ENABLED",
+    description: "Reject passwords found in known data breaches",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::AuthApiInviteOnly,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_INVITE_ONLY",
+    description: "Require an invite code to sign up (closed beta)",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::AuthApiInviteCodeTtlSeconds,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_INVITE_CODE_TTL_SECONDS",
+    description: "How long an invite code stays valid",
+    format: "positive whole number",
+    default: Some("604800"),
+  },
+  FieldMeta {
+    field: Field::AuthApiUsernameMinLength,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_USERNAME_MIN_LENGTH",
+    description: "Minimum username length",
+    format: "positive whole number",
+    default: Some("3"),
+  },
+  FieldMeta {
+    field: Field::AuthApiUsernameMaxLength,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_USERNAME_MAX_LENGTH",
+    description: "Maximum username length (at least the minimum)",
+    format: "non-negative whole number",
+    default: Some("32"),
+  },
+  FieldMeta {
+    field: Field::AuthApiReservedUsernames,
+    section: Section::AuthApi,
+    var: "DPS_AUTH_API_RESERVED_USERNAMES",
+    description: "Usernames nobody may register (case-insensitive)",
+    format: "comma-separated usernames",
+    default: Some("admin,administrator,api,root,support,system"),
+  },
+  FieldMeta {
+    field: Field::StoragePresignedUrlTtlSeconds,
+    section: Section::Storage,
+    var: "DPS_STORAGE_PRESIGNED_URL_TTL_SECONDS",
+    description: "Lifetime of presigned upload/download URLs (max 7 days)",
+    format: "positive whole number",
+    default: Some("900"),
+  },
+  FieldMeta {
+    field: Field::ImageproxyUrl,
+    section: Section::ImageProcessing,
+    var: "DPS_IMAGEPROXY_URL",
+    description: "Base URL of the image processing proxy",
+    format: "text",
+    default: Some("https://img.{domain}"),
+  },
+  FieldMeta {
+    field: Field::ImageproxySigningKey,
+    section: Section::ImageProcessing,
+    var: "DPS_IMAGEPROXY_SIGNING_KEY",
+    description: "Key used to sign image proxy URLs",
+    format: "hex",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::ImageproxySalt,
+    section: Section::ImageProcessing,
+    var: "DPS_IMAGEPROXY_SALT",
+    description: "Salt prepended to image proxy paths before signing",
+    format: "hex",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::ImageMaxDimensions,
+    section: Section::ImageProcessing,
+    var: "DPS_IMAGE_MAX_DIMENSIONS",
+    description: "Maximum output size of processed images",
+    format: "\"WIDTHxHEIGHT\"",
+    default: Some("2048x2048"),
+  },
+  FieldMeta {
+    field: Field::MediaMaxUploadBytes,
+    section: Section::Media,
+    var: "DPS_MEDIA_MAX_UPLOAD_BYTES",
+    description: "Largest file upload endpoints accept, in bytes",
+    format: "positive whole number",
+    default: Some("10485760"),
+  },
+  FieldMeta {
+    field: Field::MediaAllowedMimeTypes,
+    section: Section::Media,
+    var: "DPS_MEDIA_ALLOWED_MIME_TYPES",
+    description: "MIME types upload endpoints accept",
+    format: "comma-separated MIME types",
+    default: Some("image/jpeg,image/png,image/webp,image/gif"),
+  },
+  FieldMeta {
+    field: Field::MediaImageMaxPixels,
+    section: Section::Media,
+    var: "DPS_MEDIA_IMAGE_MAX_PIXELS",
+    description: "Most pixels (width times height) an uploaded image may have",
+    format: "positive whole number",
+    default: Some("25000000"),
+  },
+  FieldMeta {
+    field: Field::AssetVersion,
+    section: Section::StaticAssets,
+    var: "DPS_ASSET_VERSION",
+    description: "Version appended to asset URLs for cache busting",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AppVersion,
+    section: Section::BuildInfo,
+    var: "DPS_APP_VERSION",
+    description: "Application version",
+    format: "semantic version (MAJOR.MINOR.PATCH)",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::BuildSha,
+    section: Section::BuildInfo,
+    var: "DPS_BUILD_SHA",
+    description: "Git commit SHA the binary was built from",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::BuildTimestamp,
+    section: Section::BuildInfo,
+    var: "DPS_BUILD_TIMESTAMP",
+    description: "Time the binary was built, passed through as-is",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AdminApiToken,
+    section: Section::AdminApi,
+    var: "DPS_ADMIN_API_TOKEN",
+    description: "Bearer token guarding internal admin endpoints (secret)",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::InternalAuthSecret,
+    section: Section::InternalServices,
+    var: "DPS_INTERNAL_AUTH_SECRET",
+    description: "Secret shared by DPS services to authenticate calls to each other",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::InternalMtlsEnabled,
+    section: Section::InternalServices,
+    var: "DPS_INTERNAL_MTLS_ENABLED",
+    description: "Use mutual TLS for calls between services",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::InternalCaCertPath,
+    section: Section::InternalServices,
+    var: "DPS_INTERNAL_CA_CERT_PATH",
+    description: "CA certificate used to verify peer services",
+    format: "file system path",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::InternalClientCertPath,
+    section: Section::InternalServices,
+    var: "DPS_INTERNAL_CLIENT_CERT_PATH",
+    description: "Client certificate presented to peer services",
+    format: "file system path",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::InternalClientKeyPath,
+    section: Section::InternalServices,
+    var: "DPS_INTERNAL_CLIENT_KEY_PATH",
+    description: "Private key for the client certificate",
+    format: "file system path",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::ContainerDnsMode,
+    section: Section::InternalServices,
+    var: "DPS_CONTAINER_DNS_MODE",
+    description: "Internal URLs use container service names instead of the public domain",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::ContainerServiceNameTemplate,
+    section: Section::InternalServices,
+    var: "DPS_CONTAINER_SERVICE_NAME_TEMPLATE",
+    description: "Container service name, with {service} replaced by e.g. auth-api",
+    format: "text",
+    default: Some("dps-{service}"),
+  },
+  FieldMeta {
+    field: Field::DiscoveryMode,
+    section: Section::InternalServices,
+    var: "DPS_DISCOVERY_MODE",
+    description: "How internal service endpoints are located (srv requires the discovery feature)",
+    format: "\"static\" or \"srv\"",
+    default: Some("static"),
+  },
+  FieldMeta {
+    field: Field::LoadBalancerStrategy,
+    section: Section::InternalServices,
+    var: "DPS_LOAD_BALANCER_STRATEGY",
+    description: "Strategy for client-side load balancing across replicas",
+    format: "\"round-robin\", \"random\" or \"least-connections\"",
+    default: Some("round-robin"),
+  },
+  FieldMeta {
+    field: Field::StickySessionsEnabled,
+    section: Section::RealtimeGateway,
+    var: "DPS_STICKY_SESSIONS_ENABLED",
+    description: "Pin websocket clients to one gateway replica via an affinity cookie",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::StickySessionCookieName,
+    section: Section::RealtimeGateway,
+    var: "DPS_STICKY_SESSION_COOKIE_NAME",
+    description: "Name of the session affinity cookie",
+    format: "text",
+    default: Some("dps_affinity"),
+  },
+  FieldMeta {
+    field: Field::WsHeartbeatIntervalSeconds,
+    section: Section::RealtimeGateway,
+    var: "DPS_WS_HEARTBEAT_INTERVAL_SECONDS",
+    description: "How often websocket clients are pinged",
+    format: "positive whole number",
+    default: Some("30"),
+  },
+  FieldMeta {
+    field: Field::WsClientTimeoutSeconds,
+    section: Section::RealtimeGateway,
+    var: "DPS_WS_CLIENT_TIMEOUT_SECONDS",
+    description: "Silence after which a client is disconnected (must exceed the heartbeat interval)",
+    format: "positive whole number",
+    default: Some("60"),
+  },
+  FieldMeta {
+    field: Field::WsMaxMessageSizeBytes,
+    section: Section::RealtimeGateway,
+    var: "DPS_WS_MAX_MESSAGE_SIZE_BYTES",
+    description: "Largest accepted websocket message",
+    format: "non-negative whole number",
+    default: Some("65536"),
+  },
+  FieldMeta {
+    field: Field::AcmeEnabled,
+    section: Section::Acme,
+    var: "DPS_ACME_ENABLED",
+    description: "Provision TLS certificates through ACME (e.g. Let's Encrypt)",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::AcmeContactEmail,
+    section: Section::Acme,
+    var: "DPS_ACME_CONTACT_EMAIL",
+    description: "Contact email registered with the ACME account",
+    format: "text",
+    default: Some("admin@{domain}"),
+  },
+  FieldMeta {
+    field: Field::AcmeDirectoryUrl,
+    section: Section::Acme,
+    var: "DPS_ACME_DIRECTORY_URL",
+    description: "Directory URL of the ACME server",
+    format: "text",
+    default: Some("https://acme-v02.api.letsencrypt.org/directory"),
+  },
+  FieldMeta {
+    field: Field::TraceSampleRatio,
+    section: Section::Observability,
+    var: "DPS_TRACE_SAMPLE_RATIO",
+    description: "Fraction of traces to sample",
+    format: "number between 0.0 and 1.0",
+    default: Some("1.0"),
+  },
+  FieldMeta {
+    field: Field::MetricsEnabled,
+    section: Section::Observability,
+    var: "DPS_METRICS_ENABLED",
+    description: "Export metrics",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::LogSamplingRate,
+    section: Section::Observability,
+    var: "DPS_LOG_SAMPLING_RATE",
+    description: "Fraction of log records to keep",
+    format: "number between 0.0 and 1.0",
+    default: Some("1.0"),
+  },
+  FieldMeta {
+    field: Field::LogLevelOverrides,
+    section: Section::Observability,
+    var: "DPS_LOG_LEVEL_OVERRIDES",
+    description: "Per-target log levels, e.g. \"sqlx=warn,tower_http=debug\"",
+    format: "comma-separated TARGET=LEVEL pairs",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AuditLogEnabled,
+    section: Section::AuditLog,
+    var: "DPS_AUDIT_LOG_ENABLED",
+    description: "Write an audit log",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::AuditLogSink,
+    section: Section::AuditLog,
+    var: "DPS_AUDIT_LOG_SINK",
+    description: "Where audit log records are written",
+    format: "\"file\", \"stdout\" or \"syslog\"",
+    default: Some("file"),
+  },
+  FieldMeta {
+    field: Field::AuditLogPath,
+    section: Section::AuditLog,
+    var: "DPS_AUDIT_LOG_PATH",
+    description: "Audit log file for the \"file\" sink",
+    format: "file system path",
+    default: Some("{data_dir}/audit.log"),
+  },
+  FieldMeta {
+    field: Field::AuditLogRetentionDays,
+    section: Section::AuditLog,
+    var: "DPS_AUDIT_LOG_RETENTION_DAYS",
+    description: "Days audit log records are kept",
+    format: "positive whole number",
+    default: Some("365"),
+  },
+  FieldMeta {
+    field: Field::UserDataRetentionDays,
+    section: Section::DataRetention,
+    var: "DPS_USER_DATA_RETENTION_DAYS",
+    description: "Days inactive users' personal data is kept (at least 30)",
+    format: "positive whole number",
+    default: Some("730"),
+  },
+  FieldMeta {
+    field: Field::DeletedAccountPurgeDelayDays,
+    section: Section::DataRetention,
+    var: "DPS_DELETED_ACCOUNT_PURGE_DELAY_DAYS",
+    description: "Days a deleted account's data is kept before it is purged (1 to 30)",
+    format: "positive whole number",
+    default: Some("14"),
+  },
+  FieldMeta {
+    field: Field::BackupsEnabled,
+    section: Section::Backups,
+    var: "DPS_BACKUPS_ENABLED",
+    description: "Run the SQLite and asset backup tasks",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::BackupEncryptionKey,
+    section: Section::Backups,
+    var: "DPS_BACKUP_ENCRYPTION_KEY",
+    description: "Key backups are encrypted with (secret; required when backups are enabled outside development mode)",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::RateLimitOverrides,
+    section: Section::RateLimiting,
+    var: "DPS_RATE_LIMIT_",
+    description: "Per-route rate limit, keyed by the lowercase variable suffix",
+    format: "\"COUNT/UNIT\" (units: \"ms\", \"s\"/\"sec\", \"min\", \"hour\", \"day\", optionally with a multiple such as \"30s\")",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::OutboundRateLimits,
+    section: Section::RateLimiting,
+    var: "DPS_OUTBOUND_RATE_",
+    description: "Rate limit on outbound calls to a third-party integration, keyed by the lowercase variable suffix",
+    format: "\"COUNT/UNIT\" (units: \"ms\", \"s\"/\"sec\", \"min\", \"hour\", \"day\", optionally with a multiple such as \"30s\")",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::FeatureFlags,
+    section: Section::FeatureFlags,
+    var: "DPS_FEATURE_",
+    description: "Feature flag, keyed by the lowercase variable suffix",
+    format: "\"Y\", \"N\" or a rollout percentage such as \"25%\"",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::ReadOnlyMode,
+    section: Section::IncidentResponse,
+    var: "DPS_READ_ONLY_MODE",
+    description: "Reject writes while still serving reads",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::DegradedSubsystems,
+    section: Section::IncidentResponse,
+    var: "DPS_DEGRADED_SUBSYSTEMS",
+    description: "Subsystems to disable or degrade, e.g. \"signup,payments\"",
+    format: "comma-separated list",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::WaitForDependencies,
+    section: Section::Startup,
+    var: "DPS_WAIT_FOR_DEPENDENCIES",
+    description: "Wait for dependency endpoints to accept connections before starting",
+    format: "\"Y\" for true, anything else for false",
+    default: Some("false"),
+  },
+  FieldMeta {
+    field: Field::DependencyWaitTimeoutSeconds,
+    section: Section::Startup,
+    var: "DPS_DEPENDENCY_WAIT_TIMEOUT_SECONDS",
+    description: "Seconds to wait for dependencies before giving up (at least 1)",
+    format: "positive whole number",
+    default: Some("60"),
+  },
+  FieldMeta {
+    field: Field::DeclaredDependencies,
+    section: Section::Startup,
+    var: "DPS_DEPENDENCIES_",
+    description: "Services a service depends on, e.g. DPS_DEPENDENCIES_GAME_API=auth_api,redis",
+    format: "comma-separated list",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::ServiceUrls,
+    section: Section::Startup,
+    var: "DPS_SERVICE_URL_",
+    description: "Address of a backing service, e.g. DPS_SERVICE_URL_REDIS=redis://redis:6379",
+    format: "URL",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::KeyVaultRetries,
+    section: Section::Startup,
+    var: "DPS_KEY_VAULT_RETRIES",
+    description: "How many times a failed Azure Key Vault fetch is retried",
+    format: "non-negative whole number",
+    default: Some("2"),
+  },
+  FieldMeta {
+    field: Field::KeyVaultTimeoutSeconds,
+    section: Section::Startup,
+    var: "DPS_KEY_VAULT_TIMEOUT_SECONDS",
+    description: "Seconds a single Azure Key Vault fetch may take before it is abandoned",
+    format: "non-negative whole number",
+    default: Some("10"),
+  },
+  FieldMeta {
+    field: Field::GameServerRegions,
+    section: Section::GameServers,
+    var: "DPS_GAME_SERVER_REGIONS",
+    description: "Regions the dedicated game server fleet runs in",
+    format: "comma-separated list",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::GameServerPortRange,
+    section: Section::GameServers,
+    var: "DPS_GAME_SERVER_PORT_RANGE",
+    description: "Ports game server instances may bind, both ends included",
+    format: "\"START-END\" or a single port",
+    default: Some("7000-7100"),
+  },
+  FieldMeta {
+    field: Field::GameServerMaxPlayers,
+    section: Section::GameServers,
+    var: "DPS_GAME_SERVER_MAX_PLAYERS",
+    description: "Most players one game server instance accepts (at least 1)",
+    format: "positive whole number",
+    default: Some("64"),
+  },
+  FieldMeta {
+    field: Field::MatchmakingSkillWindow,
+    section: Section::Matchmaking,
+    var: "DPS_MATCHMAKING_SKILL_WINDOW",
+    description: "Skill rating difference initially accepted, widened by as much per interval (at least 1)",
+    format: "positive whole number",
+    default: Some("100"),
+  },
+  FieldMeta {
+    field: Field::MatchmakingExpandIntervalSeconds,
+    section: Section::Matchmaking,
+    var: "DPS_MATCHMAKING_EXPAND_INTERVAL_SECONDS",
+    description: "Seconds between widenings of a waiting player's skill window (at least 1)",
+    format: "positive whole number",
+    default: Some("10"),
+  },
+  FieldMeta {
+    field: Field::MatchmakingMaxWaitSeconds,
+    section: Section::Matchmaking,
+    var: "DPS_MATCHMAKING_MAX_WAIT_SECONDS",
+    description: "Seconds a player waits for a match before giving up (at least the expand interval)",
+    format: "non-negative whole number",
+    default: Some("120"),
+  },
+  FieldMeta {
+    field: Field::LeaderboardApiSubdomain,
+    section: Section::LeaderboardApi,
+    var: "DPS_LEADERBOARD_API_SUBDOMAIN",
+    description: "Subdomain the leaderboard API is served from",
+    format: "string",
+    default: Some("leaderboard"),
+  },
+  FieldMeta {
+    field: Field::LeaderboardApiPort,
+    section: Section::LeaderboardApi,
+    var: "DPS_LEADERBOARD_API_PORT",
+    description: "Port for the leaderboard API (omitted from URL if unset)",
+    format: "whole number from 0 to 65535",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::LeaderboardApiProtocol,
+    section: Section::LeaderboardApi,
+    var: "DPS_LEADERBOARD_API_PROTOCOL",
+    description: "Protocol for the leaderboard API",
+    format: "\"http\" or \"https\"",
+    default: Some("{auth_api_protocol}"),
+  },
+  FieldMeta {
+    field: Field::LeaderboardCacheTtlSeconds,
+    section: Section::LeaderboardApi,
+    var: "DPS_LEADERBOARD_CACHE_TTL_SECONDS",
+    description: "Seconds leaderboard pages may be served from cache (0 disables caching)",
+    format: "non-negative whole number",
+    default: Some("60"),
+  },
+  FieldMeta {
+    field: Field::LeaderboardPageSize,
+    section: Section::LeaderboardApi,
+    var: "DPS_LEADERBOARD_PAGE_SIZE",
+    description: "Entries per leaderboard page (at least 1)",
+    format: "positive whole number",
+    default: Some("50"),
+  },
+  FieldMeta {
+    field: Field::NotificationsApiSubdomain,
+    section: Section::NotificationsApi,
+    var: "DPS_NOTIFICATIONS_API_SUBDOMAIN",
+    description: "Subdomain the notifications API is served from",
+    format: "string",
+    default: Some("notifications"),
+  },
+  FieldMeta {
+    field: Field::NotificationsApiPort,
+    section: Section::NotificationsApi,
+    var: "DPS_NOTIFICATIONS_API_PORT",
+    description: "Port for the notifications API (omitted from URL if unset)",
+    format: "whole number from 0 to 65535",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::NotificationsApiProtocol,
+    section: Section::NotificationsApi,
+    var: "DPS_NOTIFICATIONS_API_PROTOCOL",
+    description: "Protocol for the notifications API",
+    format: "\"http\" or \"https\"",
+    default: Some("{auth_api_protocol}"),
+  },
+  FieldMeta {
+    field: Field::NotificationsBatchSize,
+    section: Section::NotificationsApi,
+    var: "DPS_NOTIFICATIONS_BATCH_SIZE",
+    description: "Notifications dispatched per batch (at least 1)",
+    format: "positive whole number",
+    default: Some("100"),
+  },
+  FieldMeta {
+    field: Field::NotificationsRetryLimit,
+    section: Section::NotificationsApi,
+    var: "DPS_NOTIFICATIONS_RETRY_LIMIT",
+    description: "Retries of a failed delivery before it is dropped (0 disables retries)",
+    format: "non-negative whole number",
+    default: Some("5"),
+  },
+  FieldMeta {
+    field: Field::TurnServerUrls,
+    section: Section::VoiceRtc,
+    var: "DPS_TURN_SERVER_URLS",
+    description: "TURN servers voice chat clients relay media through",
+    format: "comma-separated list of turn: or turns: URLs",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::TurnUsername,
+    section: Section::VoiceRtc,
+    var: "DPS_TURN_USERNAME",
+    description: "Username clients authenticate to the TURN servers with",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::TurnCredential,
+    section: Section::VoiceRtc,
+    var: "DPS_TURN_CREDENTIAL",
+    description: "Credential clients authenticate to the TURN servers with (secret)",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::StunServerUrls,
+    section: Section::VoiceRtc,
+    var: "DPS_STUN_SERVER_URLS",
+    description: "STUN servers clients discover their public address with",
+    format: "comma-separated list of stun: or stuns: URLs",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::TelemetryIngestSubdomain,
+    section: Section::TelemetryIngest,
+    var: "DPS_TELEMETRY_INGEST_SUBDOMAIN",
+    description: "Subdomain the gameplay telemetry ingest service is served from",
+    format: "string",
+    default: Some("telemetry"),
+  },
+  FieldMeta {
+    field: Field::TelemetryIngestPort,
+    section: Section::TelemetryIngest,
+    var: "DPS_TELEMETRY_INGEST_PORT",
+    description: "Port for the telemetry ingest service (omitted from URL if unset)",
+    format: "whole number from 0 to 65535",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::TelemetryIngestProtocol,
+    section: Section::TelemetryIngest,
+    var: "DPS_TELEMETRY_INGEST_PROTOCOL",
+    description: "Protocol for the telemetry ingest service",
+    format: "\"http\" or \"https\"",
+    default: Some("{auth_api_protocol}"),
+  },
+  FieldMeta {
+    field: Field::TelemetryIngestMaxBatchBytes,
+    section: Section::TelemetryIngest,
+    var: "DPS_TELEMETRY_INGEST_MAX_BATCH_BYTES",
+    description: "Largest telemetry batch accepted in one request, in bytes (at least 1)",
+    format: "positive whole number",
+    default: Some("1048576"),
+  },
+  FieldMeta {
+    field: Field::TelemetryRetentionDays,
+    section: Section::TelemetryIngest,
+    var: "DPS_TELEMETRY_RETENTION_DAYS",
+    description: "Days ingested telemetry and replays are kept (at least 1)",
+    format: "positive whole number",
+    default: Some("90"),
+  },
+  FieldMeta {
+    field: Field::AnticheatApiUrl,
+    section: Section::Anticheat,
+    var: "DPS_ANTICHEAT_API_URL",
+    description: "Base URL of the anti-cheat backend (required unless enforcement is off)",
+    format: "URL",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AnticheatApiKey,
+    section: Section::Anticheat,
+    var: "DPS_ANTICHEAT_API_KEY",
+    description: "Key the game API authenticates to the anti-cheat backend with (secret)",
+    format: "text",
+    default: None,
+  },
+  FieldMeta {
+    field: Field::AnticheatEnforcementMode,
+    section: Section::Anticheat,
+    var: "DPS_ANTICHEAT_ENFORCEMENT_MODE",
+    description: "What the game API does with anti-cheat verdicts",
+    format: "\"off\", \"log\" or \"enforce\"",
+    default: Some("off"),
+  },
+];
+
+/// Looks up the metadata for `var`, matching prefixed variables by prefix.
+pub(crate) fn describe(var: &str) -> Option<FieldMeta> {
+  FIELDS
+    .iter()
+    .find(|meta| {
+      meta.var == var
+        || (meta.var.ends_with('_') && var.len() > meta.var.len() && var.starts_with(meta.var))
+    })
+    .copied()
+}
+
+/// Returns whether the variable `var` holds a secret (see
+/// [`Field::is_secret`]), such as `DPS_AUTH_API_SESSION_SECRET` or
+/// `DPS_AUTH_API_KEY_<CLIENT>`. `_FILE` variants name files and do not.
+pub(crate) fn is_secret_var(var: &str) -> bool {
+  !var.ends_with("_FILE") && describe(var).is_some_and(|meta| meta.field.is_secret())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_describe() {
+    for meta in FIELDS {
+      assert_eq!(describe(meta.var).map(|m| m.field), Some(meta.field));
+    }
+    assert_eq!(
+      describe("DPS_RATE_LIMIT_LOGIN").map(|m| m.field),
+      Some(Field::RateLimitOverrides)
+    );
+    assert_eq!(
+      describe("DPS_FEATURE_NEW_LOGIN").map(|m| m.field),
+      Some(Field::FeatureFlags)
+    );
+    assert!(describe("DPS_UNKNOWN").is_none());
+    assert!(describe("DPS_AUTH_API_PORT_X").is_none());
+  }
+}
@@ -0,0 +1,114 @@
+//! [`figment::Provider`] implementation for [`DpsConfig`] (`figment`
+//! feature).
+
+use ::figment::value::{Dict, Map, Value};
+use ::figment::{Error, Metadata, Profile, Provider};
+
+use crate::export::ExportValue;
+use crate::DpsConfig;
+
+/// Provides the explicitly set values of a [`DpsConfig`] (see
+/// [`DpsConfig::is_explicitly_set`]) in the default profile, so figment
+/// sources merged before it keep the values DPS does not set.
+///
+/// Keys are the environment variable names without `DPS_`, in lowercase
+/// (`DPS_AUTH_API_PORT` is `auth_api_port`). Booleans, numbers and lists
+/// keep their types, per-key variables such as `DPS_RATE_LIMIT_<ROUTE>`
+/// become dictionaries (`rate_limit.login`), rate limits are
+/// `{count, per_seconds}` dictionaries, the session secret expiry is a Unix
+/// timestamp in seconds and the image dimensions a `[width, height]` array.
+/// Everything else is a string in its environment variable format.
+///
+/// ```rust
+/// use dps_config::DpsConfig;
+/// use figment::Figment;
+///
+/// let config = DpsConfig::from_map([("DPS_AUTH_API_PORT", "3000")]);
+/// let figment = Figment::new().merge(config);
+/// assert_eq!(figment.extract_inner::<u16>("auth_api_port").unwrap(), 3000);
+/// assert!(figment.find_value("domain").is_err());
+/// ```
+impl Provider for DpsConfig {
+  fn metadata(&self) -> Metadata {
+    Metadata::named("DPS config")
+  }
+
+  fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+    let dict = self
+      .export()
+      .into_iter()
+      .map(|(key, value)| (key, figment_value(value)))
+      .collect();
+    Ok(Profile::Default.collect(dict))
+  }
+}
+
+fn figment_value(value: ExportValue) -> Value {
+  match value {
+    ExportValue::Bool(value) => value.into(),
+    ExportValue::Number(value) => value.into(),
+    ExportValue::Float(value) => value.into(),
+    ExportValue::String(value) => value.into(),
+    ExportValue::List(values) => values
+      .into_iter()
+      .map(figment_value)
+      .collect::<Vec<_>>()
+      .into(),
+    ExportValue::Table(entries) => entries
+      .into_iter()
+      .map(|(key, value)| (key, figment_value(value)))
+      .collect::<Dict>()
+      .into(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ::figment::providers::Serialized;
+  use ::figment::Figment;
+
+  #[test]
+  fn test_data() {
+    let config = DpsConfig::from_map([
+      ("DPS_DEVELOPMENT_MODE", "Y"),
+      (
+        "DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS",
+        "a.example.com, b.example.com",
+      ),
+      ("DPS_IMAGE_MAX_DIMENSIONS", "1920x1080"),
+      ("DPS_RATE_LIMIT_LOGIN", "10/min"),
+      ("DPS_FEATURE_NEW_EDITOR", "25%"),
+      ("DPS_AUDIT_LOG_SINK", "syslog"),
+    ]);
+    let figment = Figment::from(Serialized::default("domain", "app.example.com")).merge(config);
+    let get = |key: &str| figment.find_value(key).unwrap();
+    assert_eq!(get("domain").as_str(), Some("app.example.com"));
+    assert_eq!(get("development_mode").to_bool(), Some(true));
+    assert_eq!(
+      figment
+        .extract_inner::<Vec<String>>("auth_api_allowed_redirect_hosts")
+        .unwrap(),
+      ["a.example.com", "b.example.com"]
+    );
+    assert_eq!(
+      figment
+        .extract_inner::<[u32; 2]>("image_max_dimensions")
+        .unwrap(),
+      [1920, 1080]
+    );
+    assert_eq!(
+      figment
+        .extract_inner::<u64>("rate_limit.login.per_seconds")
+        .unwrap(),
+      60
+    );
+    assert_eq!(get("feature.new_editor").as_str(), Some("25%"));
+    assert_eq!(get("audit_log_sink").as_str(), Some("syslog"));
+    assert!(figment.find_value("auth_api_port").is_err());
+    assert_eq!(
+      figment.find_metadata("development_mode").unwrap().name,
+      "DPS config"
+    );
+  }
+}
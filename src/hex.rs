@@ -0,0 +1,34 @@
+//! Minimal hex decoding (RFC 4648 base16).
+
+/// Decodes hex digits in either case. Returns `None` for an odd number of
+/// digits or characters that are not hex digits.
+pub(crate) fn decode(encoded: &str) -> Option<Vec<u8>> {
+  if !encoded.len().is_multiple_of(2) {
+    return None;
+  }
+  encoded
+    .as_bytes()
+    .chunks(2)
+    .map(|pair| {
+      let high = char::from(pair[0]).to_digit(16)?;
+      let low = char::from(pair[1]).to_digit(16)?;
+      Some((high << 4 | low) as u8)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_decode() {
+    assert_eq!(decode("").as_deref(), Some(&b""[..]));
+    assert_eq!(decode("736563726574").as_deref(), Some(&b"secret"[..]));
+    assert_eq!(decode("68656C6C6F").as_deref(), Some(&b"hello"[..]));
+    assert_eq!(decode("00ff").as_deref(), Some(&[0x00, 0xff][..]));
+    assert!(decode("abc").is_none());
+    assert!(decode("zz").is_none());
+    assert!(decode("+1").is_none());
+  }
+}
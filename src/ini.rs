@@ -0,0 +1,158 @@
+//! Loading INI files from legacy deployments by mapping their keys onto the
+//! `DPS_*` environment variables read by [`DpsConfig`](crate::DpsConfig).
+//!
+//! `[section]` headers, `key = value` (or `key: value`) pairs and full-line
+//! `;` or `#` comments are supported. Keys before any header or under
+//! `[global]` are top-level settings. Values are taken as-is, except for
+//! surrounding quotes, ` ;` or ` #` comments after unquoted values, and
+//! `true` and `false`, which become `"Y"` and `"N"`.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::env_file;
+use crate::env_snapshot::EnvVars;
+use crate::ConfigError;
+
+/// Reads `path` (verifying its checksum like env files), and the files it
+/// `extends`, and returns the equivalent environment variables.
+pub(crate) fn read(path: &Path) -> Result<EnvVars, ConfigError> {
+  env_file::read_extending(path, vars)
+}
+
+/// Converts INI `contents` into environment variables: `port` in section
+/// `[auth_api]` becomes `DPS_AUTH_API_PORT`, and `domain` in `[global]`
+/// becomes `DPS_DOMAIN`. Errors carry the 1-based line number.
+pub(crate) fn vars(contents: &str) -> Result<EnvVars, (usize, &'static str)> {
+  let mut vars = EnvVars::new();
+  let mut section = String::new();
+  for (index, line) in contents.lines().enumerate() {
+    let error = |reason| (index + 1, reason);
+    let line = line.trim();
+    if line.is_empty() || line.starts_with([';', '#']) {
+      continue;
+    }
+    if let Some(header) = line.strip_prefix('[') {
+      let name = header
+        .strip_suffix(']')
+        .map(str::trim)
+        .filter(|name| is_key(name))
+        .ok_or_else(|| error("invalid section header"))?;
+      section = match name.eq_ignore_ascii_case("global") {
+        true => String::new(),
+        false => name.replace(['.', '-'], "_"),
+      };
+      continue;
+    }
+    let (key, value) = line
+      .split_once(['=', ':'])
+      .ok_or_else(|| error("expected `key = value`"))?;
+    let key = key.trim();
+    if !is_key(key) {
+      return Err(error("invalid key"));
+    }
+    let value = parse_value(value.trim()).ok_or_else(|| error("unterminated quoted value"))?;
+    let name = match section.as_str() {
+      "" => format!("DPS_{key}"),
+      section => format!("DPS_{section}_{key}"),
+    };
+    vars.insert(
+      OsString::from(name.replace(['.', '-'], "_").to_ascii_uppercase()),
+      OsString::from(value),
+    );
+  }
+  Ok(vars)
+}
+
+fn is_key(key: &str) -> bool {
+  !key.is_empty()
+    && key
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.'))
+}
+
+/// Strips quotes or a trailing comment from `value`, returning `None` for
+/// an unterminated quote.
+fn parse_value(value: &str) -> Option<String> {
+  if let Some(quote @ ('"' | '\'')) = value.chars().next() {
+    let (quoted, rest) = value[1..].split_once(quote)?;
+    let rest = rest.trim_start();
+    return (rest.is_empty() || rest.starts_with([';', '#'])).then(|| quoted.to_string());
+  }
+  let end = value
+    .find([';', '#'])
+    .filter(|&i| value[..i].ends_with([' ', '\t']))
+    .unwrap_or(value.len());
+  let value = value[..end].trim_end();
+  Some(match value {
+    _ if value.eq_ignore_ascii_case("true") => "Y".to_string(),
+    _ if value.eq_ignore_ascii_case("false") => "N".to_string(),
+    _ => value.to_string(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn var(parsed: &EnvVars, key: &str) -> Option<String> {
+    parsed
+      .get(&OsString::from(key))
+      .map(|v| v.to_string_lossy().into_owned())
+  }
+
+  #[test]
+  fn test_vars() {
+    let contents = "
+      ; Legacy settings
+      environment_subdomain_suffix = -staging
+
+      [global]
+      domain = example.com ; production domain
+      development_mode = TRUE
+
+      [auth_api]
+      port: 3000
+      session_secret = \"s3cret;#\"
+      allowed_origins = https://a.test,https://b.test#x
+
+      [rate-limit]
+      login = '10/min'
+    ";
+    let parsed = vars(contents).unwrap();
+    assert_eq!(parsed.len(), 7);
+    assert_eq!(
+      var(&parsed, "DPS_ENVIRONMENT_SUBDOMAIN_SUFFIX").as_deref(),
+      Some("-staging")
+    );
+    assert_eq!(var(&parsed, "DPS_DOMAIN").as_deref(), Some("example.com"));
+    assert_eq!(var(&parsed, "DPS_DEVELOPMENT_MODE").as_deref(), Some("Y"));
+    assert_eq!(var(&parsed, "DPS_AUTH_API_PORT").as_deref(), Some("3000"));
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET").as_deref(),
+      Some("s3cret;#")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_ALLOWED_ORIGINS").as_deref(),
+      Some("https://a.test,https://b.test#x")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_RATE_LIMIT_LOGIN").as_deref(),
+      Some("10/min")
+    );
+
+    assert_eq!(
+      vars("[auth api]").unwrap_err(),
+      (1, "invalid section header")
+    );
+    assert_eq!(
+      vars("\n[a]\nport").unwrap_err(),
+      (3, "expected `key = value`")
+    );
+    assert_eq!(vars("a b = 1").unwrap_err(), (1, "invalid key"));
+    assert_eq!(
+      vars("a = \"x").unwrap_err(),
+      (1, "unterminated quoted value")
+    );
+  }
+}
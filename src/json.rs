@@ -0,0 +1,429 @@
+//! Loading JSON config documents with the same key layout as `dps.toml`: keys
+//! map onto the `DPS_*` environment variables read by
+//! [`DpsConfig`](crate::DpsConfig).
+//!
+//! The document must be an object whose values are strings, numbers,
+//! booleans, `null` or objects of those (one level deep). Arrays are
+//! rejected.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::env_file;
+use crate::env_snapshot::EnvVars;
+use crate::ConfigError;
+
+/// Reads `path` (verifying its checksum like env files), and the files it
+/// `extends`, and returns the equivalent environment variables.
+pub(crate) fn read(path: &Path) -> Result<EnvVars, ConfigError> {
+  env_file::read_extending(path, vars)
+}
+
+/// Converts a JSON document into environment variables: `port` inside
+/// `"auth_api"` becomes `DPS_AUTH_API_PORT`, booleans become `"Y"` or `"N"`
+/// and `null` values are left out. Errors carry the 1-based line number.
+pub(crate) fn vars(contents: &str) -> Result<EnvVars, (usize, &'static str)> {
+  let mut parser = Parser {
+    input: contents,
+    pos: 0,
+  };
+  let mut vars = EnvVars::new();
+  parser
+    .object(&mut |path, value| {
+      vars.insert(
+        OsString::from(format!("DPS_{}", path.join("_")).to_ascii_uppercase()),
+        OsString::from(value),
+      );
+    })
+    .map_err(|reason| (parser.line(), reason))?;
+  parser.skip_whitespace();
+  if parser.pos < contents.len() {
+    return Err((parser.line(), "unexpected trailing characters"));
+  }
+  Ok(vars)
+}
+
+/// Returns the non-null scalar members of the object found by following
+/// `path` from the top-level object (e.g. `["data", "data"]` for a Vault KV
+/// response), skipping every other value. Booleans become `"Y"` or `"N"`.
+#[cfg(feature = "vault")]
+pub(crate) fn members_at(
+  contents: &str,
+  path: &[&str],
+) -> Result<Vec<(String, String)>, (usize, &'static str)> {
+  let mut parser = Parser {
+    input: contents,
+    pos: 0,
+  };
+  let mut found = Vec::new();
+  parser
+    .find(path, &mut found)
+    .map_err(|reason| (parser.line(), reason))?;
+  Ok(found)
+}
+
+/// The scalar members of an object, in document order.
+#[cfg(feature = "etcd")]
+type Members = Vec<(String, String)>;
+
+/// Returns the non-null scalar members of every object in the array at the
+/// top-level member `key` (e.g. the `kvs` of an etcd range response), or
+/// nothing when the member is missing.
+#[cfg(feature = "etcd")]
+pub(crate) fn objects_in(contents: &str, key: &str) -> Result<Vec<Members>, (usize, &'static str)> {
+  let mut parser = Parser {
+    input: contents,
+    pos: 0,
+  };
+  let mut objects = Vec::new();
+  parser
+    .expect('{', "expected an object")
+    .and_then(|()| {
+      parser.members(&mut |parser, member| {
+        parser.skip_whitespace();
+        match member == key && parser.peek() == Some('[') {
+          true => parser.objects(&mut objects),
+          false => parser.skip_value(),
+        }
+      })
+    })
+    .map_err(|reason| (parser.line(), reason))?;
+  Ok(objects)
+}
+
+struct Parser<'a> {
+  input: &'a str,
+  pos: usize,
+}
+
+impl Parser<'_> {
+  fn line(&self) -> usize {
+    self.input[..self.pos].matches('\n').count() + 1
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.input[self.pos..].chars().next()
+  }
+
+  fn skip_whitespace(&mut self) {
+    while let Some(c) = self.peek().filter(|c| c.is_ascii_whitespace()) {
+      self.pos += c.len_utf8();
+    }
+  }
+
+  fn expect(&mut self, expected: char, reason: &'static str) -> Result<(), &'static str> {
+    self.skip_whitespace();
+    if self.peek() != Some(expected) {
+      return Err(reason);
+    }
+    self.pos += 1;
+    Ok(())
+  }
+
+  /// Parses the top-level object, calling `emit` with the key path and value
+  /// of every non-null scalar.
+  fn object(&mut self, emit: &mut dyn FnMut(&[&str], String)) -> Result<(), &'static str> {
+    self.expect('{', "expected an object")?;
+    self.members(&mut |parser, key| {
+      parser.skip_whitespace();
+      if parser.peek() == Some('{') {
+        parser.pos += 1;
+        return parser.members(&mut |parser, child| {
+          if let Some(value) = parser.scalar()? {
+            emit(&[key, child], value);
+          }
+          Ok(())
+        });
+      }
+      if let Some(value) = parser.scalar()? {
+        emit(&[key], value);
+      }
+      Ok(())
+    })
+  }
+
+  /// Parses an object, collecting the scalar members of the object at `path`.
+  #[cfg(feature = "vault")]
+  fn find(&mut self, path: &[&str], found: &mut Vec<(String, String)>) -> Result<(), &'static str> {
+    self.expect('{', "expected an object")?;
+    self.members(&mut |parser, key| {
+      parser.skip_whitespace();
+      let nested = matches!(parser.peek(), Some('{' | '['));
+      match path.split_first() {
+        Some((first, rest)) if *first == key && parser.peek() == Some('{') => {
+          parser.find(rest, found)
+        }
+        None if !nested => {
+          if let Some(value) = parser.scalar()? {
+            found.push((key.to_string(), value));
+          }
+          Ok(())
+        }
+        _ => parser.skip_value(),
+      }
+    })
+  }
+
+  /// Parses an array of objects, collecting the scalar members of each.
+  #[cfg(feature = "etcd")]
+  fn objects(&mut self, objects: &mut Vec<Members>) -> Result<(), &'static str> {
+    self.expect('[', "expected an array")?;
+    self.skip_whitespace();
+    if self.peek() == Some(']') {
+      self.pos += 1;
+      return Ok(());
+    }
+    loop {
+      let mut members = Vec::new();
+      self.expect('{', "expected an object")?;
+      self.members(&mut |parser, key| {
+        parser.skip_whitespace();
+        if matches!(parser.peek(), Some('{' | '[')) {
+          return parser.skip_value();
+        }
+        if let Some(value) = parser.scalar()? {
+          members.push((key.to_string(), value));
+        }
+        Ok(())
+      })?;
+      objects.push(members);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.pos += 1,
+        Some(']') => {
+          self.pos += 1;
+          return Ok(());
+        }
+        _ => return Err("expected `,` or `]`"),
+      }
+    }
+  }
+
+  /// Skips any value, including nested objects and arrays.
+  #[cfg(any(feature = "vault", feature = "etcd"))]
+  fn skip_value(&mut self) -> Result<(), &'static str> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('{') => {
+        self.pos += 1;
+        self.members(&mut |parser, _| parser.skip_value())
+      }
+      Some('[') => {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+          self.pos += 1;
+          return Ok(());
+        }
+        loop {
+          self.skip_value()?;
+          self.skip_whitespace();
+          match self.peek() {
+            Some(',') => self.pos += 1,
+            Some(']') => {
+              self.pos += 1;
+              return Ok(());
+            }
+            _ => return Err("expected `,` or `]`"),
+          }
+        }
+      }
+      _ => self.scalar().map(|_| ()),
+    }
+  }
+
+  /// Parses `"key": value` pairs up to the closing brace, the opening brace
+  /// having been consumed.
+  fn members(
+    &mut self,
+    member: &mut dyn FnMut(&mut Self, &str) -> Result<(), &'static str>,
+  ) -> Result<(), &'static str> {
+    self.skip_whitespace();
+    if self.peek() == Some('}') {
+      self.pos += 1;
+      return Ok(());
+    }
+    loop {
+      self.skip_whitespace();
+      let key = self.string()?;
+      self.expect(':', "expected `:`")?;
+      member(self, &key)?;
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.pos += 1,
+        Some('}') => {
+          self.pos += 1;
+          return Ok(());
+        }
+        _ => return Err("expected `,` or `}`"),
+      }
+    }
+  }
+
+  /// Parses a string, number, boolean or `null` (returned as `None`).
+  fn scalar(&mut self) -> Result<Option<String>, &'static str> {
+    self.skip_whitespace();
+    let rest = &self.input[self.pos..];
+    for (literal, value) in [("true", Some("Y")), ("false", Some("N")), ("null", None)] {
+      if rest.starts_with(literal) {
+        self.pos += literal.len();
+        return Ok(value.map(str::to_string));
+      }
+    }
+    match self.peek() {
+      Some('"') => self.string().map(Some),
+      Some('-' | '0'..='9') => {
+        let len = rest
+          .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+          .unwrap_or(rest.len());
+        self.pos += len;
+        Ok(Some(rest[..len].to_string()))
+      }
+      Some('{') => Err("objects may only be nested one level deep"),
+      Some('[') => Err("arrays are not supported"),
+      _ => Err("expected a value"),
+    }
+  }
+
+  fn string(&mut self) -> Result<String, &'static str> {
+    if self.peek() != Some('"') {
+      return Err("expected a string");
+    }
+    self.pos += 1;
+    let mut out = String::new();
+    let mut chars = self.input[self.pos..].char_indices();
+    while let Some((i, c)) = chars.next() {
+      match c {
+        '"' => {
+          self.pos += i + 1;
+          return Ok(out);
+        }
+        '\\' => out.push(match chars.next().map(|(_, c)| c) {
+          Some('"') => '"',
+          Some('\\') => '\\',
+          Some('/') => '/',
+          Some('n') => '\n',
+          Some('t') => '\t',
+          Some('r') => '\r',
+          Some('u') => {
+            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+            u32::from_str_radix(&hex, 16)
+              .ok()
+              .and_then(char::from_u32)
+              .ok_or("invalid unicode escape")?
+          }
+          _ => return Err("invalid escape"),
+        }),
+        c => out.push(c),
+      }
+    }
+    Err("unterminated string")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn var(parsed: &EnvVars, key: &str) -> Option<String> {
+    parsed
+      .get(&OsString::from(key))
+      .map(|v| v.to_string_lossy().into_owned())
+  }
+
+  #[test]
+  fn test_vars() {
+    let contents = r#"{
+      "domain": "example.com",
+      "development_mode": true,
+      "imageproxy_url": null,
+      "auth_api": {"port": 3000, "session_secret": "a\"bé", "insecure_cookie": false},
+      "rate_limit": {}
+    }"#;
+    let parsed = vars(contents).unwrap();
+    assert_eq!(parsed.len(), 5);
+    assert_eq!(var(&parsed, "DPS_DOMAIN").as_deref(), Some("example.com"));
+    assert_eq!(var(&parsed, "DPS_DEVELOPMENT_MODE").as_deref(), Some("Y"));
+    assert_eq!(var(&parsed, "DPS_IMAGEPROXY_URL"), None);
+    assert_eq!(var(&parsed, "DPS_AUTH_API_PORT").as_deref(), Some("3000"));
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET").as_deref(),
+      Some("a\"bé")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_INSECURE_COOKIE").as_deref(),
+      Some("N")
+    );
+
+    assert!(vars("{}").unwrap().is_empty());
+    assert_eq!(vars("[]").unwrap_err(), (1, "expected an object"));
+    assert_eq!(
+      vars("{\n\"ports\": [1]}").unwrap_err(),
+      (2, "arrays are not supported")
+    );
+    assert_eq!(
+      vars("{\"a\": {\"b\": {}}}").unwrap_err(),
+      (1, "objects may only be nested one level deep")
+    );
+    assert_eq!(
+      vars("{\"a\": 1} x").unwrap_err(),
+      (1, "unexpected trailing characters")
+    );
+    assert_eq!(vars("{\"a\": 1").unwrap_err(), (1, "expected `,` or `}`"));
+  }
+
+  #[cfg(feature = "vault")]
+  #[test]
+  fn test_members_at() {
+    let contents = r#"{
+      "request_id": "1",
+      "data": {
+        "data": {"session_secret": "s3cret", "replicas": 2, "tags": ["a"], "note": null},
+        "metadata": {"version": 3, "custom_metadata": {"owner": "ops"}, "destroyed": false}
+      },
+      "warnings": [{"a": [1, 2]}, []]
+    }"#;
+    assert_eq!(
+      members_at(contents, &["data", "data"]).unwrap(),
+      vec![
+        ("session_secret".to_string(), "s3cret".to_string()),
+        ("replicas".to_string(), "2".to_string()),
+      ]
+    );
+    assert!(members_at(contents, &["missing"]).unwrap().is_empty());
+    assert_eq!(
+      members_at("{\"a\": [1 2]}", &[]).unwrap_err(),
+      (1, "expected `,` or `]`")
+    );
+  }
+
+  #[cfg(feature = "etcd")]
+  #[test]
+  fn test_objects_in() {
+    let contents = r#"{
+      "header": {"revision": "7"},
+      "kvs": [
+        {"key": "a2V5", "value": "dmFsdWU=", "version": "1", "lease": null},
+        {"key": "b3RoZXI=", "extra": {"nested": [1]}}
+      ],
+      "count": "2"
+    }"#;
+    assert_eq!(
+      objects_in(contents, "kvs").unwrap(),
+      vec![
+        vec![
+          ("key".to_string(), "a2V5".to_string()),
+          ("value".to_string(), "dmFsdWU=".to_string()),
+          ("version".to_string(), "1".to_string()),
+        ],
+        vec![("key".to_string(), "b3RoZXI=".to_string())],
+      ]
+    );
+    assert!(objects_in("{\"header\": {}}", "kvs").unwrap().is_empty());
+    assert!(objects_in("{\"kvs\": []}", "kvs").unwrap().is_empty());
+    assert_eq!(
+      objects_in("{\"kvs\": [1]}", "kvs").unwrap_err(),
+      (1, "expected an object")
+    );
+  }
+}
@@ -0,0 +1,223 @@
+//! Resolving Azure Key Vault secret URIs in secret-typed variables with the
+//! `az` CLI (`azure-key-vault` feature). Credentials come from the usual
+//! `az login`, managed identity or service principal environment.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::env_snapshot::EnvVars;
+use crate::field;
+
+/// Host suffixes of the Key Vault endpoints in the public and sovereign
+/// Azure clouds.
+const HOST_SUFFIXES: [&str; 3] = [
+  ".vault.azure.net",
+  ".vault.azure.cn",
+  ".vault.usgovcloudapi.net",
+];
+
+/// Retry and timeout settings for fetching Key Vault secrets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyVaultOptions {
+  /// How many times a failed fetch is retried, with a doubling delay
+  /// starting at 500 ms.
+  pub retries: u32,
+  /// How long a single fetch may take before it is abandoned.
+  pub timeout: Duration,
+}
+
+impl Default for KeyVaultOptions {
+  fn default() -> Self {
+    Self {
+      retries: 2,
+      timeout: Duration::from_secs(10),
+    }
+  }
+}
+
+/// Returns `value` if it is a Key Vault secret URI such as
+/// `https://dps-prod.vault.azure.net/secrets/session-secret`, optionally
+/// followed by a version.
+pub(crate) fn reference(value: &OsStr) -> Option<&str> {
+  let uri = value.to_str()?;
+  let (host, path) = uri.strip_prefix("https://")?.split_once('/')?;
+  let name = path.strip_prefix("secrets/")?;
+  (HOST_SUFFIXES.iter().any(|suffix| host.ends_with(suffix)) && !name.is_empty()).then_some(uri)
+}
+
+/// Returns a copy of `vars` where every secret-typed variable holding a Key
+/// Vault URI is replaced by the secret returned by `fetch`. Each URI is
+/// fetched once.
+pub(crate) fn resolve(
+  vars: &EnvVars,
+  fetch: &mut dyn FnMut(&str) -> io::Result<String>,
+) -> io::Result<EnvVars> {
+  let mut fetched: HashMap<String, String> = HashMap::new();
+  let mut resolved = vars.clone();
+  for (var, value) in resolved.iter_mut() {
+    if !var.to_str().is_some_and(field::is_secret_var) {
+      continue;
+    }
+    let Some(uri) = reference(value).map(str::to_string) else {
+      continue;
+    };
+    let secret = match fetched.get(&uri) {
+      Some(secret) => secret.clone(),
+      None => {
+        let secret = fetch(&uri)
+          .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", var.to_string_lossy())))?;
+        fetched.insert(uri, secret.clone());
+        secret
+      }
+    };
+    *value = OsString::from(secret);
+  }
+  Ok(resolved)
+}
+
+/// Calls `fetch` until it succeeds or `options.retries` retries have failed.
+/// Errors other than timeouts and CLI failures, such as a missing `az`
+/// binary, are not retried.
+pub(crate) fn with_retries(
+  options: &KeyVaultOptions,
+  mut fetch: impl FnMut() -> io::Result<String>,
+) -> io::Result<String> {
+  let mut delay = Duration::from_millis(500);
+  let mut attempt = 0;
+  loop {
+    match fetch() {
+      Err(err)
+        if attempt < options.retries
+          && matches!(err.kind(), io::ErrorKind::Other | io::ErrorKind::TimedOut) =>
+      {
+        attempt += 1;
+        thread::sleep(delay);
+        delay *= 2;
+      }
+      result => return result,
+    }
+  }
+}
+
+/// Fetches the current value of the secret at `uri` with the `az` CLI,
+/// killing it after `timeout`.
+pub(crate) fn fetch(uri: &str, timeout: Duration) -> io::Result<String> {
+  let mut child = Command::new("az")
+    .args([
+      "keyvault", "secret", "show", "--id", uri, "--query", "value", "--output", "tsv",
+    ])
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()?;
+  // Secrets are at most 25 KiB, so the CLI never blocks on a full pipe
+  // while we poll.
+  let deadline = Instant::now() + timeout;
+  while child.try_wait()?.is_none() {
+    if Instant::now() >= deadline {
+      child.kill()?;
+      child.wait()?;
+      return Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("{uri}: timed out after {}s", timeout.as_secs_f64()),
+      ));
+    }
+    thread::sleep(Duration::from_millis(20));
+  }
+  let output = child.wait_with_output()?;
+  if !output.status.success() {
+    return Err(io::Error::other(format!(
+      "{uri}: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  let secret = String::from_utf8(output.stdout)
+    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{uri}: not UTF-8")))?;
+  Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_reference() {
+    for uri in [
+      "https://dps-prod.vault.azure.net/secrets/session",
+      "https://dps-prod.vault.azure.net/secrets/session/0123abcd",
+      "https://dps.vault.usgovcloudapi.net/secrets/session",
+    ] {
+      assert_eq!(reference(OsStr::new(uri)), Some(uri));
+    }
+    for value in [
+      "s3cret",
+      "http://dps-prod.vault.azure.net/secrets/session",
+      "https://dps-prod.vault.azure.net/keys/session",
+      "https://dps-prod.vault.azure.net/secrets/",
+      "https://example.com/secrets/session",
+    ] {
+      assert_eq!(reference(OsStr::new(value)), None, "{value}");
+    }
+  }
+
+  #[test]
+  fn test_resolve() {
+    let uri = "https://dps.vault.azure.net/secrets/session";
+    let vars: EnvVars = [
+      ("DPS_AUTH_API_SESSION_SECRET", uri),
+      ("DPS_INTERNAL_AUTH_SECRET", uri),
+      (
+        "DPS_AUTH_API_KEY_BILLING",
+        "https://dps.vault.azure.net/secrets/billing",
+      ),
+      ("DPS_DOMAIN", uri),
+    ]
+    .into_iter()
+    .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+    .collect();
+    let mut calls = 0;
+    let resolved = resolve(&vars, &mut |uri| {
+      calls += 1;
+      Ok(uri.rsplit('/').next().unwrap().to_uppercase())
+    })
+    .unwrap();
+    assert_eq!(calls, 2);
+    let get = |key: &str| resolved[OsStr::new(key)].to_str().unwrap().to_string();
+    assert_eq!(get("DPS_AUTH_API_SESSION_SECRET"), "SESSION");
+    assert_eq!(get("DPS_INTERNAL_AUTH_SECRET"), "SESSION");
+    assert_eq!(get("DPS_AUTH_API_KEY_BILLING"), "BILLING");
+    assert_eq!(get("DPS_DOMAIN"), uri);
+
+    let err = resolve(&vars, &mut |_| Err(io::Error::other("Forbidden"))).unwrap_err();
+    assert!(err.to_string().contains("Forbidden"));
+  }
+
+  #[test]
+  fn test_with_retries() {
+    let options = KeyVaultOptions {
+      retries: 1,
+      timeout: Duration::from_secs(1),
+    };
+    let mut attempts = 0;
+    let result = with_retries(&options, || {
+      attempts += 1;
+      match attempts {
+        1 => Err(io::Error::new(io::ErrorKind::TimedOut, "slow")),
+        _ => Ok("s3cret".to_string()),
+      }
+    });
+    assert_eq!(result.unwrap(), "s3cret");
+
+    let mut attempts = 0;
+    let result = with_retries(&options, || {
+      attempts += 1;
+      Err(io::Error::new(io::ErrorKind::NotFound, "az not installed"))
+    });
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    assert_eq!(attempts, 1);
+  }
+}
@@ -0,0 +1,43 @@
+//! Client-side load balancing strategy used between DPS services.
+
+use std::fmt;
+
+/// How a service spreads calls across the replicas of another service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LoadBalancerStrategy {
+  /// Cycle through replicas in order.
+  #[default]
+  RoundRobin,
+  /// Pick a replica at random for every call.
+  Random,
+  /// Pick the replica with the fewest in-flight calls.
+  LeastConnections,
+}
+
+impl LoadBalancerStrategy {
+  /// Parses the environment variable form (`"round-robin"`, `"random"`,
+  /// `"least-connections"`), returning `None` for anything else.
+  pub fn parse(value: &str) -> Option<LoadBalancerStrategy> {
+    match value {
+      "round-robin" => Some(LoadBalancerStrategy::RoundRobin),
+      "random" => Some(LoadBalancerStrategy::Random),
+      "least-connections" => Some(LoadBalancerStrategy::LeastConnections),
+      _ => None,
+    }
+  }
+
+  /// Returns the environment variable form of the strategy.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      LoadBalancerStrategy::RoundRobin => "round-robin",
+      LoadBalancerStrategy::Random => "random",
+      LoadBalancerStrategy::LeastConnections => "least-connections",
+    }
+  }
+}
+
+impl fmt::Display for LoadBalancerStrategy {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
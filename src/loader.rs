@@ -0,0 +1,114 @@
+//! Composing a [`DpsConfig`] from layered sources with fixed precedence.
+
+use std::path::{Path, PathBuf};
+
+use crate::env_snapshot::EnvVars;
+#[cfg(feature = "etcd")]
+use crate::EtcdSource;
+use crate::{ConfigError, DpsConfig, EnvLoader};
+
+type Override = Box<dyn FnOnce(&mut DpsConfig)>;
+
+/// Builds a [`DpsConfig`] from optional layers, each overriding the values
+/// set by the ones before it:
+///
+/// 1. defaults given with [`DpsConfigLoader::defaults`],
+/// 2. config files given with [`DpsConfigLoader::file`], in call order,
+/// 3. etcd sources given with `DpsConfigLoader::etcd` (`etcd` feature), in
+///    call order,
+/// 4. the environment snapshot, when [`DpsConfigLoader::env`] is called,
+/// 5. overrides given with [`DpsConfigLoader::override_with`], in call order.
+///
+/// The precedence does not depend on the order the layers are added in.
+/// Values no layer sets keep the built-in defaults, so a loader with no
+/// layers builds the same config as an empty environment.
+///
+/// ```rust
+/// use dps_config::{DpsConfig, DpsConfigLoader};
+///
+/// DpsConfig::replace_env_snapshot([("DPS_AUTH_API_PORT", "3000")]);
+/// let mut defaults: DpsConfig = "DPS_DOMAIN=example.com".parse().unwrap();
+/// defaults.set_auth_api_port(Some(8080));
+/// let config = DpsConfigLoader::new()
+///   .override_with(|c| c.set_auth_api_subdomain("login"))
+///   .env()
+///   .defaults(defaults)
+///   .build()
+///   .unwrap();
+/// assert_eq!(config.get_auth_api_authority(), "login.example.com:3000");
+/// ```
+#[derive(Default)]
+pub struct DpsConfigLoader {
+  defaults: Option<DpsConfig>,
+  files: Vec<PathBuf>,
+  #[cfg(feature = "etcd")]
+  etcd: Vec<EtcdSource>,
+  env: bool,
+  overrides: Vec<Override>,
+}
+
+impl DpsConfigLoader {
+  /// Creates a loader with no layers.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Uses the values set in `defaults` as the lowest-precedence layer.
+  pub fn defaults(mut self, defaults: DpsConfig) -> Self {
+    self.defaults = Some(defaults);
+    self
+  }
+
+  /// Adds a config file layer, read like `DpsConfig::try_from(path)`. Later
+  /// files override earlier ones.
+  pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+    self.files.push(path.as_ref().to_path_buf());
+    self
+  }
+
+  /// Adds an etcd layer, read with [`EtcdSource::read`]. Later sources
+  /// override earlier ones.
+  #[cfg(feature = "etcd")]
+  pub fn etcd(mut self, source: EtcdSource) -> Self {
+    self.etcd.push(source);
+    self
+  }
+
+  /// Adds the environment snapshot layer, read like [`DpsConfig::new`].
+  pub fn env(mut self) -> Self {
+    self.env = true;
+    self
+  }
+
+  /// Adds an explicit override, applied after every other layer.
+  pub fn override_with(mut self, apply: impl FnOnce(&mut DpsConfig) + 'static) -> Self {
+    self.overrides.push(Box::new(apply));
+    self
+  }
+
+  /// Builds the config, failing when a config file cannot be read or parsed,
+  /// or with [`ConfigError::SourceRead`] when an etcd source cannot be read.
+  pub fn build(self) -> Result<DpsConfig, ConfigError> {
+    let mut config = DpsConfig::load(&mut EnvLoader::from_vars(EnvVars::new()));
+    if let Some(defaults) = self.defaults {
+      config.overlay(defaults);
+    }
+    for path in &self.files {
+      config.overlay(DpsConfig::try_from(path.as_path())?);
+    }
+    #[cfg(feature = "etcd")]
+    for source in &self.etcd {
+      config.overlay(source.read().map_err(|err| ConfigError::SourceRead {
+        source: source.endpoint().to_string(),
+        reason: err.to_string(),
+      })?);
+    }
+    if self.env {
+      config.overlay(DpsConfig::new());
+    }
+    for apply in self.overrides {
+      apply(&mut config);
+    }
+    Ok(config)
+  }
+}
@@ -0,0 +1,9 @@
+//! Server entries for generated OpenAPI documents.
+
+/// An entry of an OpenAPI document's `servers` list, returned by
+/// [`DpsConfig::get_openapi_servers`](crate::DpsConfig::get_openapi_servers).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiServer {
+  pub url: String,
+  pub description: &'static str,
+}
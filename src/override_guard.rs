@@ -0,0 +1,54 @@
+//! Temporary setter overrides that are reverted when the guard is dropped.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::DpsConfig;
+
+/// Borrows a [`DpsConfig`] with overrides applied and restores its previous
+/// state on drop. Created by [`DpsConfig::scoped_override`].
+///
+/// ```rust
+/// use dps_config::DpsConfig;
+///
+/// let mut config = DpsConfig::new();
+/// config.set_domain("example.com");
+/// {
+///   let config = config.scoped_override(|c| c.set_domain("test.local"));
+///   assert_eq!(config.get_domain(), "test.local");
+/// }
+/// assert_eq!(config.get_domain(), "example.com");
+/// ```
+pub struct OverrideGuard<'a> {
+  config: &'a mut DpsConfig,
+  original: Option<DpsConfig>,
+}
+
+impl<'a> OverrideGuard<'a> {
+  pub(crate) fn new(config: &'a mut DpsConfig, apply: impl FnOnce(&mut DpsConfig)) -> Self {
+    let original = Some(config.clone());
+    apply(config);
+    Self { config, original }
+  }
+}
+
+impl Deref for OverrideGuard<'_> {
+  type Target = DpsConfig;
+
+  fn deref(&self) -> &DpsConfig {
+    self.config
+  }
+}
+
+impl DerefMut for OverrideGuard<'_> {
+  fn deref_mut(&mut self) -> &mut DpsConfig {
+    self.config
+  }
+}
+
+impl Drop for OverrideGuard<'_> {
+  fn drop(&mut self) {
+    if let Some(original) = self.original.take() {
+      *self.config = original;
+    }
+  }
+}
@@ -0,0 +1,14 @@
+//! The password rules the Auth API enforces on sign-up and password reset.
+
+/// Password requirements, returned by
+/// [`DpsConfig::get_password_policy`](crate::DpsConfig::get_password_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PasswordPolicy {
+  /// Minimum number of characters.
+  pub min_length: u32,
+  /// Whether passwords must contain a character that is neither a letter
+  /// nor a digit.
+  pub require_symbols: bool,
+  /// Whether passwords are checked against known data breaches.
+  pub breach_check_enabled: bool,
+}
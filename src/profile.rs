@@ -0,0 +1,51 @@
+//! Deployment profiles selecting profile-specific defaults and config files.
+
+use std::fmt;
+
+/// The kind of deployment a config is for.
+///
+/// Profiles change the defaults of a few properties: `development_mode`
+/// and `auth_api_insecure_cookie` default to `true` for
+/// [`Profile::Development`] and [`Profile::Test`], and to `false` for
+/// [`Profile::Production`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+  Development,
+  Test,
+  Production,
+}
+
+impl Profile {
+  /// Parses the environment variable form (`"development"`, `"test"` or
+  /// `"production"`, or the short `"dev"` and `"prod"`), returning `None`
+  /// for anything else.
+  pub fn parse(value: &str) -> Option<Profile> {
+    match value {
+      "development" | "dev" => Some(Profile::Development),
+      "test" => Some(Profile::Test),
+      "production" | "prod" => Some(Profile::Production),
+      _ => None,
+    }
+  }
+
+  /// Returns the name of the profile, as used in `dps.{profile}.toml`.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Profile::Development => "development",
+      Profile::Test => "test",
+      Profile::Production => "production",
+    }
+  }
+
+  /// Returns whether development-only defaults apply: `true` for
+  /// [`Profile::Development`] and [`Profile::Test`].
+  pub fn is_development(&self) -> bool {
+    matches!(self, Profile::Development | Profile::Test)
+  }
+}
+
+impl fmt::Display for Profile {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
@@ -0,0 +1,104 @@
+//! Reverse proxy configuration generated from the service hosts.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// A public host served by the proxy and the internal address it forwards to.
+pub(crate) struct ProxyRoute {
+  pub(crate) host: String,
+  /// `host:port` of the service.
+  pub(crate) upstream: String,
+  /// Certificate and key for HTTPS, or `None` to serve plain HTTP.
+  pub(crate) tls: Option<(PathBuf, PathBuf)>,
+}
+
+/// Renders one Caddyfile site block per route. HTTPS sites rely on Caddy's
+/// automatic certificates; HTTP sites are prefixed with `http://`.
+pub(crate) fn caddyfile(routes: &[ProxyRoute]) -> String {
+  let mut out = String::new();
+  for (i, route) in routes.iter().enumerate() {
+    if i > 0 {
+      out.push('\n');
+    }
+    let scheme = if route.tls.is_some() { "" } else { "http://" };
+    let _ = writeln!(out, "{scheme}{} {{", route.host);
+    let _ = writeln!(out, "\treverse_proxy {}", route.upstream);
+    out.push_str("}\n");
+  }
+  out
+}
+
+/// Renders one nginx `server` block per route.
+pub(crate) fn nginx_server_blocks(routes: &[ProxyRoute]) -> String {
+  let mut out = String::new();
+  for (i, route) in routes.iter().enumerate() {
+    if i > 0 {
+      out.push('\n');
+    }
+    out.push_str("server {\n");
+    match &route.tls {
+      Some((cert, key)) => {
+        out.push_str("    listen 443 ssl;\n");
+        let _ = writeln!(out, "    server_name {};", route.host);
+        let _ = writeln!(out, "    ssl_certificate {};", cert.display());
+        let _ = writeln!(out, "    ssl_certificate_key {};", key.display());
+      }
+      None => {
+        out.push_str("    listen 80;\n");
+        let _ = writeln!(out, "    server_name {};", route.host);
+      }
+    }
+    out.push_str("\n    location / {\n");
+    let _ = writeln!(out, "        proxy_pass http://{};", route.upstream);
+    out.push_str("        proxy_set_header Host $host;\n");
+    out.push_str("        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n");
+    out.push_str("        proxy_set_header X-Forwarded-Proto $scheme;\n");
+    out.push_str("    }\n}\n");
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn routes() -> Vec<ProxyRoute> {
+    vec![
+      ProxyRoute {
+        host: "auth.example.com".to_string(),
+        upstream: "127.0.0.1:3000".to_string(),
+        tls: Some(("certs/a.crt".into(), "certs/a.key".into())),
+      },
+      ProxyRoute {
+        host: "api.example.com".to_string(),
+        upstream: "dps-api:80".to_string(),
+        tls: None,
+      },
+    ]
+  }
+
+  #[test]
+  fn test_caddyfile() {
+    assert_eq!(
+      caddyfile(&routes()),
+      "auth.example.com {\n\treverse_proxy 127.0.0.1:3000\n}\n\
+       \n\
+       http://api.example.com {\n\treverse_proxy dps-api:80\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_nginx_server_blocks() {
+    let blocks = nginx_server_blocks(&routes());
+    assert!(blocks.starts_with(
+      "server {\n    listen 443 ssl;\n    server_name auth.example.com;\n    \
+       ssl_certificate certs/a.crt;\n    ssl_certificate_key certs/a.key;\n\n    \
+       location / {\n        proxy_pass http://127.0.0.1:3000;\n"
+    ));
+    assert!(blocks.contains(
+      "server {\n    listen 80;\n    server_name api.example.com;\n\n    \
+       location / {\n        proxy_pass http://dps-api:80;\n"
+    ));
+    assert_eq!(blocks.matches("server {").count(), 2);
+  }
+}
@@ -0,0 +1,126 @@
+//! Per-route rate limits parsed from `COUNT/UNIT` strings.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Allows `count` requests per `per` window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RateLimit {
+  pub count: u32,
+  pub per: Duration,
+}
+
+/// Units accepted in `COUNT/UNIT`, largest first, with their length in
+/// milliseconds.
+const UNITS: [(&str, u64); 5] = [
+  ("day", 86_400_000),
+  ("hour", 3_600_000),
+  ("min", 60_000),
+  ("s", 1000),
+  ("ms", 1),
+];
+
+impl RateLimit {
+  /// Parses `COUNT/UNIT` (e.g. `"10/min"`), where `UNIT` is one of `ms`,
+  /// `s`, `sec`, `second`, `m`, `min`, `minute`, `h`, `hour` or `day`,
+  /// optionally preceded by a multiple (`"10/30s"`). Returns `None` for
+  /// anything else.
+  pub fn parse(value: &str) -> Option<RateLimit> {
+    let (count, window) = value.split_once('/')?;
+    let window = window.trim();
+    let digits = window.len()
+      - window
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .len();
+    let multiple = match digits {
+      0 => 1,
+      _ => window[..digits].parse().ok()?,
+    };
+    let millis: u64 = match window[digits..].trim() {
+      "ms" => 1,
+      "s" | "sec" | "second" => 1000,
+      "m" | "min" | "minute" => 60_000,
+      "h" | "hour" => 3_600_000,
+      "day" => 86_400_000,
+      _ => return None,
+    };
+    Some(RateLimit {
+      count: count.trim().parse().ok()?,
+      per: Duration::from_millis(millis.checked_mul(multiple)?),
+    })
+  }
+}
+
+/// Formats the limit as `COUNT/UNIT` in the largest unit that divides the
+/// window, with a multiple where needed (`"10/min"`, `"10/30s"`), so it
+/// parses back into the same limit. Windows are kept to the millisecond.
+impl fmt::Display for RateLimit {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let millis = u64::try_from(self.per.as_millis()).unwrap_or(u64::MAX);
+    let (unit, length) = UNITS
+      .into_iter()
+      .find(|(_, length)| millis >= *length && millis % length == 0)
+      .unwrap_or(("s", 1000));
+    match millis / length {
+      1 => write!(f, "{}/{unit}", self.count),
+      multiple => write!(f, "{}/{multiple}{unit}", self.count),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(
+      RateLimit::parse("10/min"),
+      Some(RateLimit {
+        count: 10,
+        per: Duration::from_secs(60)
+      })
+    );
+    assert_eq!(
+      RateLimit::parse(" 5 / s "),
+      Some(RateLimit {
+        count: 5,
+        per: Duration::from_secs(1)
+      })
+    );
+    assert_eq!(
+      RateLimit::parse("1000/day").map(|limit| limit.per),
+      Some(Duration::from_secs(86400))
+    );
+    assert_eq!(
+      RateLimit::parse("10/30s").map(|limit| limit.per),
+      Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+      RateLimit::parse("1/250ms").map(|limit| limit.per),
+      Some(Duration::from_millis(250))
+    );
+    for invalid in ["", "10", "10/week", "ten/min", "-1/min", "10/-5s", "10/5"] {
+      assert!(RateLimit::parse(invalid).is_none(), "{invalid}");
+    }
+  }
+
+  #[test]
+  fn test_display() {
+    let limit = |count, millis| RateLimit {
+      count,
+      per: Duration::from_millis(millis),
+    };
+    assert_eq!(limit(10, 60_000).to_string(), "10/min");
+    assert_eq!(limit(1000, 86_400_000).to_string(), "1000/day");
+    assert_eq!(limit(10, 30_000).to_string(), "10/30s");
+    assert_eq!(limit(5, 7_200_000).to_string(), "5/2hour");
+    assert_eq!(limit(3, 172_800_000).to_string(), "3/2day");
+    assert_eq!(limit(1, 1500).to_string(), "1/1500ms");
+    assert_eq!(limit(1, 0).to_string(), "1/0s");
+    for millis in [1, 250, 1000, 30_000, 60_000, 90_000, 3_600_000, 604_800_000] {
+      let limit = limit(7, millis);
+      assert_eq!(RateLimit::parse(&limit.to_string()), Some(limit), "{limit}");
+    }
+  }
+}
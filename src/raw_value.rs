@@ -0,0 +1,58 @@
+//! Environment values as read, kept alongside the parsed properties so errors
+//! can quote what was actually set.
+
+use std::collections::HashMap;
+
+use crate::ConfigError;
+
+/// Non-empty UTF-8 environment values read while loading, keyed by variable
+/// name.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RawValues(HashMap<String, String>);
+
+impl RawValues {
+  pub(crate) fn insert(&mut self, var: &str, value: &str) {
+    self.0.insert(var.to_string(), value.to_string());
+  }
+
+  pub(crate) fn extend(&mut self, other: RawValues) {
+    self.0.extend(other.0);
+  }
+
+  pub(crate) fn get(&self, var: &str) -> Option<&str> {
+    self.0.get(var).map(String::as_str)
+  }
+
+  /// Returns a [`ConfigError::InvalidValue`] quoting the raw value of `var`,
+  /// if one was read.
+  pub(crate) fn invalid(&self, var: &str, reason: &str) -> Option<ConfigError> {
+    Some(ConfigError::InvalidValue {
+      var: var.to_string(),
+      value: self.get(var)?.to_string(),
+      reason: reason.to_string(),
+    })
+  }
+
+  /// Returns a [`ConfigError::InvalidValue`] for the first variable (by
+  /// name) starting with `prefix` whose lowercase suffix `is_set` rejects,
+  /// i.e. a per-key variable such as `DPS_RATE_LIMIT_<ROUTE>` whose value
+  /// could not be parsed.
+  pub(crate) fn invalid_prefixed(
+    &self,
+    prefix: &str,
+    is_set: impl Fn(&str) -> bool,
+    reason: &str,
+  ) -> Option<ConfigError> {
+    let mut vars: Vec<_> = self
+      .0
+      .keys()
+      .filter(|var| {
+        var
+          .strip_prefix(prefix)
+          .is_some_and(|name| !is_set(&name.to_ascii_lowercase()))
+      })
+      .collect();
+    vars.sort();
+    self.invalid(vars.first()?, reason)
+  }
+}
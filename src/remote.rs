@@ -0,0 +1,309 @@
+//! Fetching a JSON or TOML config document over HTTPS with the `curl` CLI
+//! (`remote` feature).
+
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::env_snapshot::EnvVars;
+use crate::{json, toml, ConfigError};
+
+/// Where a config fetched by [`DpsConfig::from_url`](crate::DpsConfig::from_url)
+/// came from, kept so it can be refreshed.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteSource {
+  pub(crate) url: String,
+  pub(crate) timeout: Duration,
+  pub(crate) etag: Option<String>,
+  /// The only protocol `url` and any redirect it answers with may use.
+  pub(crate) protocol: &'static str,
+}
+
+impl RemoteSource {
+  /// A source for `url` that is only fetched over HTTPS.
+  pub(crate) fn new(url: &str, timeout: Duration) -> Self {
+    Self {
+      url: url.to_string(),
+      timeout,
+      etag: None,
+      protocol: "https",
+    }
+  }
+
+  /// Fetches the document, sending the last `ETag` in `If-None-Match`.
+  /// Returns `None` when the server answers `304 Not Modified`, and
+  /// otherwise the variables the document sets, remembering its new `ETag`.
+  pub(crate) fn fetch(&mut self) -> Result<Option<EnvVars>, ConfigError> {
+    let error = |reason: String| ConfigError::SourceRead {
+      source: self.url.clone(),
+      reason,
+    };
+    let scheme = format!("{}://", self.protocol);
+    let is_allowed = self
+      .url
+      .get(..scheme.len())
+      .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&scheme));
+    if !is_allowed {
+      return Err(error(format!("must be a {scheme} URL")));
+    }
+    let proto = format!("={}", self.protocol);
+    let mut command = Command::new("curl");
+    command
+      .args([
+        "--silent",
+        "--show-error",
+        "--location",
+        "--dump-header",
+        "-",
+      ])
+      .args(["--proto", &proto, "--proto-redir", &proto])
+      .arg("--max-time")
+      .arg(self.timeout.as_secs_f64().to_string());
+    if let Some(etag) = &self.etag {
+      command
+        .arg("--header")
+        .arg(format!("If-None-Match: {etag}"));
+    }
+    let output = command
+      .arg(&self.url)
+      .stdin(Stdio::null())
+      .output()
+      .map_err(|err| error(format!("cannot run curl: {err}")))?;
+    if !output.status.success() {
+      return Err(error(
+        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+      ));
+    }
+    let output = String::from_utf8(output.stdout).map_err(|_| error("not UTF-8".to_string()))?;
+    let (status, headers, body) =
+      response(&output).ok_or_else(|| error("malformed response".to_string()))?;
+    let header = |name: &str| {
+      headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| *value)
+    };
+    match status {
+      304 => return Ok(None),
+      200..=299 => {}
+      _ => return Err(error(format!("HTTP status {status}"))),
+    }
+    let parse = match is_json(header("Content-Type"), &self.url, body) {
+      true => json::vars,
+      false => toml::vars,
+    };
+    let vars = parse(body).map_err(|(line, reason)| ConfigError::Syntax {
+      path: None,
+      line,
+      reason: reason.to_string(),
+    })?;
+    self.etag = header("ETag").map(str::to_string);
+    Ok(Some(vars))
+  }
+}
+
+/// Response headers as `(name, value)` pairs.
+type Headers<'a> = Vec<(&'a str, &'a str)>;
+
+/// Splits curl's `--dump-header -` output into the final status code,
+/// headers and body, skipping the header blocks of redirects and interim
+/// responses.
+fn response(output: &str) -> Option<(u16, Headers<'_>, &str)> {
+  let mut rest = output;
+  let mut head = None;
+  while rest.starts_with("HTTP/") {
+    let (block, body) = rest.split_once("\r\n\r\n").unwrap_or((rest, ""));
+    head = Some(block);
+    rest = body;
+  }
+  let mut lines = head?.lines();
+  let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+  let headers = lines
+    .filter_map(|line| line.split_once(':'))
+    .map(|(key, value)| (key.trim(), value.trim()))
+    .collect();
+  Some((status, headers, rest))
+}
+
+/// Returns whether the document is JSON rather than TOML, going by its
+/// `Content-Type`, then the URL's extension, then whether it starts with
+/// `{`.
+fn is_json(content_type: Option<&str>, url: &str, body: &str) -> bool {
+  if let Some(content_type) = content_type {
+    if content_type.contains("json") {
+      return true;
+    }
+    if content_type.contains("toml") {
+      return false;
+    }
+  }
+  let path = url.split(['?', '#']).next().unwrap_or(url);
+  if path.ends_with(".json") {
+    return true;
+  }
+  if path.ends_with(".toml") {
+    return false;
+  }
+  body.trim_start().starts_with('{')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::DpsConfig;
+  use serial_test::serial;
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpListener;
+  use std::thread::{self, JoinHandle};
+
+  /// Serves `responses` (status line, headers and body), one connection
+  /// each, and returns the URL and a handle yielding each request's
+  /// `If-None-Match` header.
+  fn serve(responses: Vec<String>) -> (String, JoinHandle<Vec<Option<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/dps", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+      let mut etags = Vec::new();
+      for response in responses {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut etag = None;
+        loop {
+          let mut line = String::new();
+          reader.read_line(&mut line).unwrap();
+          if line.trim().is_empty() {
+            break;
+          }
+          if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("if-none-match") {
+              etag = Some(value.trim().to_string());
+            }
+          }
+        }
+        etags.push(etag);
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+      }
+      etags
+    });
+    (url, server)
+  }
+
+  /// Builds a config from `url` over plain HTTP, as the test servers do not
+  /// speak TLS.
+  fn from_http_url(url: &str, timeout: Duration) -> Result<DpsConfig, ConfigError> {
+    DpsConfig::from_remote(RemoteSource {
+      protocol: "http",
+      ..RemoteSource::new(url, timeout)
+    })
+  }
+
+  fn ok(content_type: &str, etag: &str, body: &str) -> String {
+    format!(
+      "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+      body.len()
+    )
+  }
+
+  #[test]
+  fn test_response() {
+    let output = "HTTP/1.1 301 Moved Permanently\r\nLocation: /dps.json\r\n\r\nHTTP/1.1 200 OK\r\nETag: \"v1\"\r\n\r\n{}";
+    let (status, headers, body) = response(output).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(headers, vec![("ETag", "\"v1\"")]);
+    assert_eq!(body, "{}");
+
+    let (status, _, body) = response("HTTP/2 304\r\n\r\n").unwrap();
+    assert_eq!((status, body), (304, ""));
+    assert!(response("not http").is_none());
+  }
+
+  #[test]
+  fn test_is_json() {
+    assert!(is_json(
+      Some("application/json; charset=utf-8"),
+      "https://c/dps",
+      ""
+    ));
+    assert!(!is_json(
+      Some("application/toml"),
+      "https://c/dps.json",
+      "{"
+    ));
+    assert!(is_json(Some("text/plain"), "https://c/dps.json?v=2", ""));
+    assert!(!is_json(None, "https://c/dps.toml", "{"));
+    assert!(is_json(None, "https://c/dps", " {\"domain\": \"x\"}"));
+    assert!(!is_json(None, "https://c/dps", "domain = \"x\""));
+  }
+
+  #[test]
+  #[serial]
+  fn test_from_url_and_refresh() {
+    let (url, server) = serve(vec![
+      ok(
+        "application/json",
+        "\"v1\"",
+        r#"{"domain": "example.com", "auth_api": {"port": 8080}}"#,
+      ),
+      "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string(),
+      ok("application/toml", "\"v2\"", "domain = \"example.org\"\n"),
+      "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        .to_string(),
+    ]);
+    DpsConfig::replace_env_snapshot([("DPS_API_PATH", "v2")]);
+    let mut config = from_http_url(&url, Duration::from_secs(10)).unwrap();
+    assert_eq!(config.get_domain(), "example.com");
+    assert_eq!(config.get_auth_api_port(), Some(8080));
+    assert_eq!(config.get_api_path(), "v2");
+    assert_eq!(config.get_remote_etag(), Some("\"v1\""));
+
+    assert!(!config.refresh_from_url().unwrap());
+    assert_eq!(config.get_domain(), "example.com");
+
+    assert!(config.refresh_from_url().unwrap());
+    assert_eq!(config.get_domain(), "example.org");
+    assert_eq!(config.get_auth_api_port(), None);
+    assert_eq!(config.get_api_path(), "v2");
+    assert_eq!(config.get_remote_etag(), Some("\"v2\""));
+
+    let err = config.refresh_from_url().err().unwrap();
+    assert!(matches!(err, ConfigError::SourceRead { .. }), "{err}");
+    assert_eq!(config.get_domain(), "example.org");
+    assert_eq!(
+      server.join().unwrap(),
+      vec![
+        None,
+        Some("\"v1\"".to_string()),
+        Some("\"v1\"".to_string()),
+        Some("\"v2\"".to_string()),
+      ]
+    );
+    DpsConfig::reset_env_snapshot();
+  }
+
+  #[test]
+  #[serial]
+  fn test_from_url_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/dps.json", listener.local_addr().unwrap());
+    let err = from_http_url(&url, Duration::from_millis(200))
+      .err()
+      .unwrap();
+    assert!(matches!(err, ConfigError::SourceRead { .. }), "{err}");
+    assert!(DpsConfig::from_map::<_, &str, &str>([]).refresh_from_url() == Ok(false));
+  }
+
+  #[test]
+  #[serial]
+  fn test_from_url_requires_https() {
+    for url in [
+      "http://127.0.0.1:1/dps.json",
+      "file:///etc/dps.json",
+      "config.example.com/dps.json",
+    ] {
+      let err = DpsConfig::from_url(url).err().unwrap();
+      assert!(
+        matches!(&err, ConfigError::SourceRead { source, reason } if source == url && reason.contains("https://")),
+        "{err}"
+      );
+    }
+  }
+}
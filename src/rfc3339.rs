@@ -0,0 +1,168 @@
+//! Minimal RFC 3339 timestamp parsing (`YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)`).
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parses an RFC 3339 timestamp, returning `None` when it is malformed or
+/// before the Unix epoch.
+pub(crate) fn parse(value: &str) -> Option<SystemTime> {
+  let bytes = value.as_bytes();
+  if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[13] != b':' {
+    return None;
+  }
+  if !matches!(bytes[10], b'T' | b't' | b' ') || bytes[16] != b':' {
+    return None;
+  }
+  let num = |range: std::ops::Range<usize>| -> Option<i64> {
+    let part = value.get(range)?;
+    part
+      .bytes()
+      .all(|b| b.is_ascii_digit())
+      .then(|| part.parse().ok())?
+  };
+  let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+  let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+  if !(1..=12).contains(&month)
+    || day < 1
+    || day > days_in_month(year, month)
+    || hour > 23
+    || minute > 59
+    || second > 60
+  {
+    return None;
+  }
+
+  let mut rest = &value[19..];
+  let mut nanos = 0u32;
+  if let Some(frac) = rest.strip_prefix('.') {
+    let digits = frac.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+      return None;
+    }
+    let padded = format!("{:0<9}", &frac[..digits.min(9)]);
+    nanos = padded.parse().ok()?;
+    rest = &frac[digits..];
+  }
+
+  let offset = match rest {
+    "Z" | "z" => 0,
+    _ => {
+      let sign = match rest.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+      };
+      let (h, m) = rest[1..].split_once(':')?;
+      if h.len() != 2 || m.len() != 2 {
+        return None;
+      }
+      let (h, m): (i64, i64) = (h.parse().ok()?, m.parse().ok()?);
+      if h > 23 || m > 59 {
+        return None;
+      }
+      sign * (h * 3600 + m * 60)
+    }
+  };
+
+  let secs =
+    days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+  let secs = u64::try_from(secs).ok()?;
+  Some(UNIX_EPOCH + Duration::new(secs, nanos))
+}
+
+/// Formats `time` as `YYYY-MM-DDTHH:MM:SSZ`, dropping fractional seconds.
+pub(crate) fn format(time: SystemTime) -> String {
+  let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+  let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+  let secs = secs.rem_euclid(86400);
+  format!(
+    "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+    secs / 3600,
+    secs % 3600 / 60,
+    secs % 60
+  )
+}
+
+fn is_leap_year(year: i64) -> bool {
+  (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+  match month {
+    2 if is_leap_year(year) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's
+/// `days_from_civil`).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let yoe = year - era * 400;
+  let mp = (month + 9) % 12;
+  let doy = (153 * mp + 2) / 5 + day - 1;
+  let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  era * 146097 + doe - 719468
+}
+
+/// Proleptic Gregorian date for days since 1970-01-01 (Howard Hinnant's
+/// `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z - era * 146097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+  (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn secs(value: &str) -> Option<u64> {
+    parse(value).map(|t| t.duration_since(UNIX_EPOCH).unwrap().as_secs())
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(secs("1970-01-01T00:00:00Z"), Some(0));
+    assert_eq!(secs("2025-11-27T12:00:00Z"), Some(1764244800));
+    assert_eq!(secs("2025-11-27T14:30:00+02:30"), Some(1764244800));
+    assert_eq!(secs("2024-02-29T00:00:00-00:00"), Some(1709164800));
+    assert_eq!(
+      parse("2025-11-27T12:00:00.25Z"),
+      Some(UNIX_EPOCH + Duration::new(1764244800, 250_000_000))
+    );
+    for invalid in [
+      "",
+      "2025-11-27",
+      "2025-11-27T12:00:00",
+      "2025-13-01T00:00:00Z",
+      "2025-02-29T00:00:00Z",
+      "2025-11-27T24:00:00Z",
+      "2025-11-27T12:00:00.Z",
+      "2025-11-27T12:00:00+0200",
+      "1969-12-31T23:59:59Z",
+    ] {
+      assert!(parse(invalid).is_none(), "{invalid}");
+    }
+  }
+
+  #[test]
+  fn test_format() {
+    for value in [
+      "1970-01-01T00:00:00Z",
+      "2024-02-29T23:59:59Z",
+      "2025-11-27T12:00:00Z",
+    ] {
+      assert_eq!(format(parse(value).unwrap()), value);
+    }
+  }
+}
@@ -0,0 +1,385 @@
+//! Loading RON config files with the same key layout as `dps.toml`: fields
+//! map onto the `DPS_*` environment variables read by
+//! [`DpsConfig`](crate::DpsConfig).
+//!
+//! The document must be a struct, optionally named (`DpsConfig(...)`), whose
+//! values are strings, characters, numbers, booleans, `None`, `Some(...)`,
+//! unit variants (read as their name in kebab case, so `RoundRobin` is
+//! `round-robin`) or structs of those (one level deep).
+//! Lists, maps and tuples are rejected.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::env_file;
+use crate::env_snapshot::EnvVars;
+use crate::ConfigError;
+
+/// Reads `path` (verifying its checksum like env files), and the files it
+/// `extends`, and returns the equivalent environment variables.
+pub(crate) fn read(path: &Path) -> Result<EnvVars, ConfigError> {
+  env_file::read_extending(path, vars)
+}
+
+/// Converts a RON document into environment variables: `port` inside
+/// `auth_api: (...)` becomes `DPS_AUTH_API_PORT`, booleans become `"Y"` or
+/// `"N"` and `None` values are left out. Errors carry the 1-based line
+/// number.
+pub(crate) fn vars(contents: &str) -> Result<EnvVars, (usize, &'static str)> {
+  let mut parser = Parser {
+    input: contents,
+    pos: 0,
+  };
+  let mut vars = EnvVars::new();
+  parser
+    .document(&mut |path, value| {
+      vars.insert(
+        OsString::from(format!("DPS_{}", path.join("_")).to_ascii_uppercase()),
+        OsString::from(value),
+      );
+    })
+    .map_err(|reason| (parser.line(), reason))?;
+  parser.skip_whitespace();
+  if parser.pos < contents.len() {
+    return Err((parser.line(), "unexpected trailing characters"));
+  }
+  Ok(vars)
+}
+
+/// A parsed value: a scalar (`None` for `None`) or the start of a struct
+/// whose opening parenthesis has been consumed.
+enum Value {
+  Scalar(Option<String>),
+  Struct,
+}
+
+struct Parser<'a> {
+  input: &'a str,
+  pos: usize,
+}
+
+impl Parser<'_> {
+  fn line(&self) -> usize {
+    self.input[..self.pos].matches('\n').count() + 1
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.input[self.pos..].chars().next()
+  }
+
+  /// Skips whitespace and `//` and `/* */` comments.
+  fn skip_whitespace(&mut self) {
+    loop {
+      let rest = &self.input[self.pos..];
+      let trimmed = rest.trim_start();
+      self.pos += rest.len() - trimmed.len();
+      if trimmed.starts_with("//") {
+        self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+      } else if trimmed.starts_with("/*") {
+        self.pos += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+      } else {
+        return;
+      }
+    }
+  }
+
+  fn expect(&mut self, expected: char, reason: &'static str) -> Result<(), &'static str> {
+    self.skip_whitespace();
+    if self.peek() != Some(expected) {
+      return Err(reason);
+    }
+    self.pos += 1;
+    Ok(())
+  }
+
+  /// Parses the top-level struct, calling `emit` with the field path and
+  /// value of every scalar that is not `None`.
+  fn document(&mut self, emit: &mut dyn FnMut(&[&str], String)) -> Result<(), &'static str> {
+    self.skip_whitespace();
+    if self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+      self.identifier()?;
+    }
+    self.expect('(', "expected a struct")?;
+    self.fields(&mut |parser, key| match parser.value()? {
+      Value::Struct => parser.fields(&mut |parser, child| match parser.value()? {
+        Value::Struct => Err("structs may only be nested one level deep"),
+        Value::Scalar(value) => {
+          if let Some(value) = value {
+            emit(&[key, child], value);
+          }
+          Ok(())
+        }
+      }),
+      Value::Scalar(value) => {
+        if let Some(value) = value {
+          emit(&[key], value);
+        }
+        Ok(())
+      }
+    })
+  }
+
+  /// Parses `field: value` pairs up to the closing parenthesis, the opening
+  /// parenthesis having been consumed. A trailing comma is allowed.
+  fn fields(
+    &mut self,
+    field: &mut dyn FnMut(&mut Self, &str) -> Result<(), &'static str>,
+  ) -> Result<(), &'static str> {
+    loop {
+      self.skip_whitespace();
+      if self.peek() == Some(')') {
+        self.pos += 1;
+        return Ok(());
+      }
+      let key = self.identifier()?;
+      self.expect(':', "expected `:`")?;
+      field(self, &key)?;
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.pos += 1,
+        Some(')') => {}
+        _ => return Err("expected `,` or `)`"),
+      }
+    }
+  }
+
+  fn identifier(&mut self) -> Result<String, &'static str> {
+    self.skip_whitespace();
+    let rest = &self.input[self.pos..];
+    let len = rest
+      .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+      .unwrap_or(rest.len());
+    if len == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+      return Err("expected a field name");
+    }
+    self.pos += len;
+    Ok(rest[..len].to_string())
+  }
+
+  fn value(&mut self) -> Result<Value, &'static str> {
+    self.skip_whitespace();
+    let rest = &self.input[self.pos..];
+    match self.peek() {
+      Some('"') => self.string().map(|s| Value::Scalar(Some(s))),
+      Some('r') if rest[1..].starts_with(['"', '#']) => {
+        self.raw_string().map(|s| Value::Scalar(Some(s)))
+      }
+      Some('\'') => self.character().map(|c| Value::Scalar(Some(c.to_string()))),
+      Some('-' | '+' | '.' | '0'..='9') => {
+        let len = rest
+          .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.' | '_')))
+          .unwrap_or(rest.len());
+        self.pos += len;
+        Ok(Value::Scalar(Some(rest[..len].replace('_', ""))))
+      }
+      Some('(') => {
+        self.pos += 1;
+        Ok(Value::Struct)
+      }
+      Some('[') => Err("lists are not supported"),
+      Some('{') => Err("maps are not supported"),
+      Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+        let name = self.identifier()?;
+        self.skip_whitespace();
+        let call = self.peek() == Some('(');
+        match name.as_str() {
+          "true" => Ok(Value::Scalar(Some("Y".to_string()))),
+          "false" => Ok(Value::Scalar(Some("N".to_string()))),
+          "None" => Ok(Value::Scalar(None)),
+          "Some" if call => {
+            self.pos += 1;
+            let Value::Scalar(value) = self.value()? else {
+              return Err("structs may only be nested one level deep");
+            };
+            self.expect(')', "expected `)`")?;
+            Ok(Value::Scalar(value))
+          }
+          _ if call => {
+            self.pos += 1;
+            Ok(Value::Struct)
+          }
+          _ => Ok(Value::Scalar(Some(kebab_case(&name)))),
+        }
+      }
+      _ => Err("expected a value"),
+    }
+  }
+
+  fn string(&mut self) -> Result<String, &'static str> {
+    self.pos += 1;
+    let mut out = String::new();
+    let mut chars = self.input[self.pos..].char_indices();
+    while let Some((i, c)) = chars.next() {
+      match c {
+        '"' => {
+          self.pos += i + 1;
+          return Ok(out);
+        }
+        '\\' => out.push(escape(&mut chars.by_ref().map(|(_, c)| c))?),
+        c => out.push(c),
+      }
+    }
+    Err("unterminated string")
+  }
+
+  /// Parses `r"..."` or `r#"..."#` (with any number of `#`).
+  fn raw_string(&mut self) -> Result<String, &'static str> {
+    let rest = &self.input[self.pos + 1..];
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    let body = rest[hashes..]
+      .strip_prefix('"')
+      .ok_or("expected a raw string")?;
+    let end = body
+      .find(&format!("\"{}", "#".repeat(hashes)))
+      .ok_or("unterminated string")?;
+    self.pos += 1 + hashes + 1 + end + 1 + hashes;
+    Ok(body[..end].to_string())
+  }
+
+  fn character(&mut self) -> Result<char, &'static str> {
+    self.pos += 1;
+    let mut chars = self.input[self.pos..].chars();
+    let c = match chars.next() {
+      Some('\\') => escape(&mut chars)?,
+      Some(c) => c,
+      None => return Err("unterminated character"),
+    };
+    if chars.next() != Some('\'') {
+      return Err("unterminated character");
+    }
+    self.pos = self.input.len() - chars.as_str().len();
+    Ok(c)
+  }
+}
+
+/// Converts a unit variant name to the form the `DPS_*` variables use, e.g.
+/// `LeastConnections` to `least-connections` and `HmacSha256` to
+/// `hmac-sha256`.
+fn kebab_case(name: &str) -> String {
+  let mut out = String::with_capacity(name.len() + 4);
+  let mut prev = None;
+  for c in name.chars() {
+    if c.is_ascii_uppercase()
+      && prev.is_some_and(|p: char| p.is_ascii_lowercase() || p.is_ascii_digit())
+    {
+      out.push('-');
+    }
+    out.push(c.to_ascii_lowercase());
+    prev = Some(c);
+  }
+  out
+}
+
+/// Decodes the escape following a backslash.
+fn escape(chars: &mut dyn Iterator<Item = char>) -> Result<char, &'static str> {
+  Ok(match chars.next() {
+    Some('"') => '"',
+    Some('\'') => '\'',
+    Some('\\') => '\\',
+    Some('n') => '\n',
+    Some('t') => '\t',
+    Some('r') => '\r',
+    Some('0') => '\0',
+    Some('u') => {
+      if chars.next() != Some('{') {
+        return Err("invalid unicode escape");
+      }
+      let hex: String = chars.take_while(|&c| c != '}').collect();
+      u32::from_str_radix(&hex, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or("invalid unicode escape")?
+    }
+    _ => return Err("invalid escape"),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn var(parsed: &EnvVars, key: &str) -> Option<String> {
+    parsed
+      .get(&OsString::from(key))
+      .map(|v| v.to_string_lossy().into_owned())
+  }
+
+  #[test]
+  fn test_vars() {
+    let contents = r##"// Shared settings
+      DpsConfig(
+        domain: "example.com",
+        development_mode: true,
+        imageproxy_url: None,
+        profile: Production,
+        auth_api: (
+          port: Some(3_000),
+          session_secret: r#"a"bé"#, /* inline */
+          insecure_cookie: false,
+          subdomain: "id\u{21}",
+        ),
+        rate_limit: (),
+      )
+    "##;
+    let parsed = vars(contents).unwrap();
+    assert_eq!(parsed.len(), 7);
+    assert_eq!(var(&parsed, "DPS_DOMAIN").as_deref(), Some("example.com"));
+    assert_eq!(var(&parsed, "DPS_DEVELOPMENT_MODE").as_deref(), Some("Y"));
+    assert_eq!(var(&parsed, "DPS_IMAGEPROXY_URL"), None);
+    assert_eq!(var(&parsed, "DPS_PROFILE").as_deref(), Some("production"));
+    assert_eq!(var(&parsed, "DPS_AUTH_API_PORT").as_deref(), Some("3000"));
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET").as_deref(),
+      Some("a\"bé")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_INSECURE_COOKIE").as_deref(),
+      Some("N")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SUBDOMAIN").as_deref(),
+      Some("id!")
+    );
+
+    assert_eq!(kebab_case("RoundRobin"), "round-robin");
+    assert_eq!(kebab_case("HmacSha256"), "hmac-sha256");
+    assert_eq!(kebab_case("Ed25519"), "ed25519");
+
+    assert!(vars("()").unwrap().is_empty());
+    assert_eq!(vars("(a: 'x', b: '\\n')").unwrap().len(), 2);
+    assert_eq!(vars("[]").unwrap_err(), (1, "expected a struct"));
+    assert_eq!(
+      vars("(\nports: [1])").unwrap_err(),
+      (2, "lists are not supported")
+    );
+    assert_eq!(
+      vars("(a: (b: ()))").unwrap_err(),
+      (1, "structs may only be nested one level deep")
+    );
+    assert_eq!(
+      vars("(a: 1) x").unwrap_err(),
+      (1, "unexpected trailing characters")
+    );
+    assert_eq!(vars("(a: 1").unwrap_err(), (1, "expected `,` or `)`"));
+    assert_eq!(vars("(a: \"x)").unwrap_err(), (1, "unterminated string"));
+  }
+
+  #[test]
+  fn test_from_ron_str_enums() {
+    let config = crate::DpsConfig::from_ron_str(
+      "(profile: Production, load_balancer_strategy: LeastConnections, \
+       audit_log_sink: Syslog, anticheat: (enforcement_mode: Enforce))",
+    )
+    .unwrap();
+    assert_eq!(config.get_profile(), crate::Profile::Production);
+    assert_eq!(
+      config.get_load_balancer_strategy(),
+      crate::LoadBalancerStrategy::LeastConnections
+    );
+    assert_eq!(config.get_audit_log_sink(), crate::AuditLogSink::Syslog);
+    assert_eq!(
+      config.get_anticheat_enforcement_mode(),
+      crate::AnticheatEnforcementMode::Enforce
+    );
+  }
+}
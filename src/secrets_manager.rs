@@ -0,0 +1,152 @@
+//! Resolving `secretsmanager://` references with AWS Secrets Manager
+//! through the `aws` CLI (`aws-secrets` feature). Credentials come from the
+//! usual AWS environment variables, profile or instance role.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::process::{Command, Stdio};
+
+use crate::env_snapshot::EnvVars;
+use crate::field;
+
+const SCHEME: &str = "secretsmanager://";
+
+/// Returns the secret id (name or ARN) referenced by `value`, if it is a
+/// `secretsmanager://` reference.
+pub(crate) fn reference(value: &OsStr) -> Option<&str> {
+  value
+    .to_str()?
+    .strip_prefix(SCHEME)
+    .filter(|id| !id.is_empty())
+}
+
+/// Returns the secret ids referenced by the secret-typed variables of
+/// `vars`.
+pub(crate) fn references(vars: &EnvVars) -> impl Iterator<Item = &str> {
+  vars
+    .iter()
+    .filter(|(var, _)| var.to_str().is_some_and(field::is_secret_var))
+    .filter_map(|(_, value)| reference(value))
+}
+
+/// Returns a copy of `vars` where every secret-typed variable holding a
+/// reference is replaced by the secret returned by `fetch`. Each secret id
+/// is fetched once.
+pub(crate) fn resolve(
+  vars: &EnvVars,
+  fetch: &mut dyn FnMut(&str) -> io::Result<String>,
+) -> io::Result<EnvVars> {
+  let mut fetched: HashMap<String, String> = HashMap::new();
+  let mut resolved = vars.clone();
+  for (var, value) in resolved.iter_mut() {
+    if !var.to_str().is_some_and(field::is_secret_var) {
+      continue;
+    }
+    let Some(id) = reference(value).map(str::to_string) else {
+      continue;
+    };
+    let secret = match fetched.get(&id) {
+      Some(secret) => secret.clone(),
+      None => {
+        let secret = fetch(&id)
+          .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", var.to_string_lossy())))?;
+        fetched.insert(id, secret.clone());
+        secret
+      }
+    };
+    *value = OsString::from(secret);
+  }
+  Ok(resolved)
+}
+
+/// Fetches the current `SecretString` of `secret_id` with the `aws` CLI.
+/// ARNs are fetched from the region they name.
+pub(crate) fn fetch(secret_id: &str) -> io::Result<String> {
+  let mut command = Command::new("aws");
+  command.args([
+    "secretsmanager",
+    "get-secret-value",
+    "--secret-id",
+    secret_id,
+    "--query",
+    "SecretString",
+    "--output",
+    "text",
+  ]);
+  if let Some(region) = region(secret_id) {
+    command.args(["--region", region]);
+  }
+  let output = command.stdin(Stdio::null()).output()?;
+  if !output.status.success() {
+    return Err(io::Error::other(format!(
+      "{secret_id}: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  let secret = String::from_utf8(output.stdout).map_err(|_| {
+    io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!("{secret_id}: not UTF-8"),
+    )
+  })?;
+  Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Returns the region of an ARN such as
+/// `arn:aws:secretsmanager:eu-west-1:123456789012:secret:dps-AbCdEf`.
+fn region(secret_id: &str) -> Option<&str> {
+  let mut parts = secret_id.strip_prefix("arn:")?.split(':');
+  parts.nth(2).filter(|region| !region.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_reference() {
+    assert_eq!(
+      reference(OsStr::new("secretsmanager://dps/session")),
+      Some("dps/session")
+    );
+    assert_eq!(reference(OsStr::new("secretsmanager://")), None);
+    assert_eq!(reference(OsStr::new("s3cret")), None);
+  }
+
+  #[test]
+  fn test_resolve() {
+    let vars: EnvVars = [
+      (
+        "DPS_AUTH_API_SESSION_SECRET",
+        "secretsmanager://dps/session",
+      ),
+      ("DPS_AUTH_API_KEY_BILLING", "secretsmanager://dps/billing"),
+      ("DPS_AUTH_API_KEY_OPS_FILE", "secretsmanager://dps/ops"),
+      ("DPS_DOMAIN", "secretsmanager://dps/domain"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.into(), v.into()))
+    .collect();
+    let mut ids: Vec<&str> = references(&vars).collect();
+    ids.sort();
+    assert_eq!(ids, ["dps/billing", "dps/session"]);
+
+    let resolved = resolve(&vars, &mut |id| Ok(format!("secret for {id}"))).unwrap();
+    let get = |var: &str| resolved[OsStr::new(var)].to_str().unwrap();
+    assert_eq!(get("DPS_AUTH_API_SESSION_SECRET"), "secret for dps/session");
+    assert_eq!(get("DPS_AUTH_API_KEY_BILLING"), "secret for dps/billing");
+    assert_eq!(get("DPS_AUTH_API_KEY_OPS_FILE"), "secretsmanager://dps/ops");
+    assert_eq!(get("DPS_DOMAIN"), "secretsmanager://dps/domain");
+  }
+
+  #[test]
+  fn test_region() {
+    assert_eq!(
+      region("arn:aws:secretsmanager:eu-west-1:123456789012:secret:dps-AbCdEf"),
+      Some("eu-west-1")
+    );
+    assert_eq!(region("dps/session"), None);
+    assert_eq!(region("arn:aws:secretsmanager"), None);
+  }
+}
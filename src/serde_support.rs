@@ -0,0 +1,354 @@
+//! [`serde::Serialize`] and [`serde::Deserialize`] implementations for
+//! [`DpsConfig`] (`serde` feature).
+
+use std::ffi::OsString;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::env_snapshot::EnvVars;
+use crate::export::{ExportValue, MASK};
+use crate::{DpsConfig, EnvLoader};
+
+/// Serializes the value of every explicitly set property
+/// ([`DpsConfig::is_explicitly_set`]), so defaults are not frozen into the
+/// output and a deserialized config keeps following them. The map is keyed
+/// by environment variable name without `DPS_`, in lowercase
+/// (`auth_api_port`). Booleans, numbers and lists keep their types,
+/// per-key variables such as `DPS_RATE_LIMIT_<ROUTE>` become nested maps
+/// and everything else is a string in its environment variable format
+/// (`"2048x2048"`, `"10/min"`, RFC 3339 timestamps), so the output can be
+/// deserialized again or read with [`DpsConfig::from_json_str`].
+///
+/// Secret values ([`Field::is_secret`](crate::Field::is_secret)) and
+/// passwords in service URLs are replaced with `"***"`; use
+/// [`DpsConfig::serialize_with_secrets`] to include them.
+impl Serialize for DpsConfig {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    Entries(self.effective_values(false, false)).serialize(serializer)
+  }
+}
+
+/// Builds a config from a map in the layout produced by its `Serialize`
+/// implementation, without reading the environment. Values may also be
+/// strings in their environment variable format, and `"***"` (a masked
+/// secret) and `null` values are left unset.
+impl<'de> Deserialize<'de> for DpsConfig {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let vars = deserializer.deserialize_map(VarsVisitor)?;
+    Ok(Self::load(&mut EnvLoader::from_vars(vars)))
+  }
+}
+
+impl DpsConfig {
+  /// Like the `Serialize` implementation of `DpsConfig`, but includes secret
+  /// values instead of masking them. Only use it where the output is
+  /// protected like the secrets themselves.
+  ///
+  /// ```rust
+  /// # #[cfg(feature = "figment")]
+  /// # {
+  /// use dps_config::DpsConfig;
+  /// use figment::{providers::Serialized, Figment};
+  ///
+  /// let config = DpsConfig::from_map([("DPS_AUTH_API_SESSION_SECRET", "s3cret")]);
+  /// let masked = Figment::from(Serialized::defaults(&config));
+  /// assert_eq!(masked.extract_inner::<String>("auth_api_session_secret").unwrap(), "***");
+  /// let revealed = Figment::from(Serialized::defaults(config.serialize_with_secrets()));
+  /// assert_eq!(revealed.extract_inner::<String>("auth_api_session_secret").unwrap(), "s3cret");
+  /// # }
+  /// ```
+  pub fn serialize_with_secrets(&self) -> impl Serialize + '_ {
+    Entries(self.effective_values(false, true))
+  }
+}
+
+struct Entries(Vec<(String, ExportValue)>);
+
+impl Serialize for Entries {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(self.0.iter().map(|(key, value)| (key, Value(value))))
+  }
+}
+
+struct Value<'a>(&'a ExportValue);
+
+impl Serialize for Value<'_> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self.0 {
+      ExportValue::Bool(value) => serializer.serialize_bool(*value),
+      ExportValue::Number(value) => serializer.serialize_u64(*value),
+      ExportValue::Float(value) => serializer.serialize_f64(*value),
+      ExportValue::String(value) => serializer.serialize_str(value),
+      ExportValue::List(values) => serializer.collect_seq(values.iter().map(Value)),
+      ExportValue::Table(entries) => {
+        serializer.collect_map(entries.iter().map(|(key, value)| (key, Value(value))))
+      }
+    }
+  }
+}
+
+/// A deserialized value in its environment variable format.
+enum Parsed {
+  Scalar(Option<String>),
+  Table(Vec<(String, Option<String>)>),
+}
+
+/// Reads the top-level map into `DPS_*` variables.
+struct VarsVisitor;
+
+impl<'de> Visitor<'de> for VarsVisitor {
+  type Value = EnvVars;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a map of DPS settings")
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<EnvVars, A::Error> {
+    let mut vars = EnvVars::new();
+    let mut insert = |key: String, value: Option<String>| {
+      if let Some(value) = value.filter(|value| value != MASK) {
+        vars.insert(
+          OsString::from(format!("DPS_{key}").to_ascii_uppercase()),
+          OsString::from(value),
+        );
+      }
+    };
+    while let Some(key) = map.next_key::<String>()? {
+      match map.next_value_seed(ParsedVisitor { nested: false })? {
+        Parsed::Scalar(value) => insert(key, value),
+        Parsed::Table(entries) => {
+          for (child, value) in entries {
+            insert(format!("{key}_{child}"), value);
+          }
+        }
+      }
+    }
+    Ok(vars)
+  }
+}
+
+/// Reads a value, allowing maps only when not `nested` in another map.
+#[derive(Clone, Copy)]
+struct ParsedVisitor {
+  nested: bool,
+}
+
+impl<'de> de::DeserializeSeed<'de> for ParsedVisitor {
+  type Value = Parsed;
+
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Parsed, D::Error> {
+    deserializer.deserialize_any(self)
+  }
+}
+
+impl<'de> Visitor<'de> for ParsedVisitor {
+  type Value = Parsed;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.nested {
+      true => f.write_str("a string, number, boolean or list"),
+      false => f.write_str("a string, number, boolean, list or map"),
+    }
+  }
+
+  fn visit_bool<E: de::Error>(self, value: bool) -> Result<Parsed, E> {
+    let value = if value { "Y" } else { "N" };
+    Ok(Parsed::Scalar(Some(value.to_string())))
+  }
+
+  fn visit_i64<E: de::Error>(self, value: i64) -> Result<Parsed, E> {
+    Ok(Parsed::Scalar(Some(value.to_string())))
+  }
+
+  fn visit_u64<E: de::Error>(self, value: u64) -> Result<Parsed, E> {
+    Ok(Parsed::Scalar(Some(value.to_string())))
+  }
+
+  fn visit_f64<E: de::Error>(self, value: f64) -> Result<Parsed, E> {
+    Ok(Parsed::Scalar(Some(value.to_string())))
+  }
+
+  fn visit_str<E: de::Error>(self, value: &str) -> Result<Parsed, E> {
+    Ok(Parsed::Scalar(Some(value.to_string())))
+  }
+
+  fn visit_none<E: de::Error>(self) -> Result<Parsed, E> {
+    Ok(Parsed::Scalar(None))
+  }
+
+  fn visit_unit<E: de::Error>(self) -> Result<Parsed, E> {
+    Ok(Parsed::Scalar(None))
+  }
+
+  fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Parsed, D::Error> {
+    deserializer.deserialize_any(self)
+  }
+
+  /// Joins list items with commas, as in list-valued variables.
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Parsed, A::Error> {
+    let mut items = Vec::new();
+    while let Some(item) = seq.next_element_seed(ParsedVisitor { nested: true })? {
+      match item {
+        Parsed::Scalar(Some(item)) => items.push(item),
+        Parsed::Scalar(None) => {}
+        Parsed::Table(_) => return Err(de::Error::custom("lists may not contain maps")),
+      }
+    }
+    Ok(Parsed::Scalar(Some(items.join(","))))
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Parsed, A::Error> {
+    if self.nested {
+      return Err(de::Error::custom("maps may only be nested one level deep"));
+    }
+    let mut entries = Vec::new();
+    while let Some(key) = map.next_key::<String>()? {
+      match map.next_value_seed(ParsedVisitor { nested: true })? {
+        Parsed::Scalar(value) => entries.push((key, value)),
+        Parsed::Table(_) => unreachable!("nested maps are rejected"),
+      }
+    }
+    Ok(Parsed::Table(entries))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::de::value::{Error, MapDeserializer};
+
+  use super::*;
+  use crate::Field;
+
+  #[test]
+  fn test_serialized_values() {
+    let config = DpsConfig::from_map([
+      ("DPS_DOMAIN", "example.com"),
+      ("DPS_AUTH_API_SESSION_SECRET", "s3cret"),
+      ("DPS_RATE_LIMIT_LOGIN", "10/30s"),
+      ("DPS_OUTBOUND_RATE_SENDGRID", "100/2hour"),
+      ("DPS_SERVICE_URL_POSTGRES", "postgres://dps:hunter2@db/dps"),
+    ]);
+    let values = config.effective_values(false, false);
+    let value = |key: &str| {
+      let (_, value) = values.iter().find(|(k, _)| k == key)?;
+      Some(value.clone())
+    };
+    assert_eq!(value("domain"), Some("example.com".into()));
+    assert_eq!(value("auth_api_session_secret"), Some("***".into()));
+    assert_eq!(
+      value("rate_limit"),
+      Some(ExportValue::Table(vec![(
+        "login".to_string(),
+        "10/30s".into()
+      )]))
+    );
+    assert_eq!(
+      value("outbound_rate"),
+      Some(ExportValue::Table(vec![(
+        "sendgrid".to_string(),
+        "100/2hour".into()
+      )]))
+    );
+    let service_url = |url: &str| ExportValue::Table(vec![("postgres".to_string(), url.into())]);
+    assert_eq!(
+      value("service_url"),
+      Some(service_url("postgres://dps:***@db/dps"))
+    );
+    // Defaults are left out
+    assert_eq!(value("api_path"), None);
+    assert_eq!(values.len(), 5);
+
+    let revealed = config.effective_values(false, true);
+    assert!(revealed.contains(&(
+      "service_url".to_string(),
+      service_url("postgres://dps:hunter2@db/dps")
+    )));
+
+    let map = MapDeserializer::<_, Error>::new(
+      [
+        ("domain", "example.com"),
+        ("auth_api_session_secret", "***"),
+        ("rate_limit_login", "10/30s"),
+      ]
+      .into_iter(),
+    );
+    let restored = DpsConfig::deserialize(map).unwrap();
+    assert_eq!(restored.get_domain(), "example.com");
+    assert_eq!(restored.get_auth_api_session_secret(), None);
+    assert_eq!(
+      restored.get_rate_limit("login"),
+      config.get_rate_limit("login")
+    );
+    assert!(!restored.is_explicitly_set(Field::ApiPath));
+  }
+
+  #[cfg(feature = "figment")]
+  #[test]
+  fn test_round_trip() {
+    use ::figment::providers::Serialized;
+    use ::figment::Figment;
+
+    let config = DpsConfig::from_map([
+      ("DPS_DOMAIN", "example.com"),
+      ("DPS_DEVELOPMENT_MODE", "Y"),
+      ("DPS_AUTH_API_PORT", "3000"),
+      ("DPS_AUTH_API_SESSION_SECRET", "s3cret"),
+      (
+        "DPS_AUTH_API_SESSION_SECRET_EXPIRES_AT",
+        "2026-01-31T00:00:00Z",
+      ),
+      ("DPS_AUTH_API_KEY_BILLING", "key-1"),
+      (
+        "DPS_AUTH_API_ALLOWED_REDIRECT_HOSTS",
+        "a.example.com, b.example.com",
+      ),
+      ("DPS_IMAGE_MAX_DIMENSIONS", "1920x1080"),
+      ("DPS_RATE_LIMIT_LOGIN", "10/min"),
+    ]);
+    let figment = Figment::from(Serialized::defaults(&config));
+    let string = |key: &str| figment.extract_inner::<String>(key).unwrap();
+    assert_eq!(string("auth_api_session_secret"), "***");
+    assert_eq!(string("auth_api_key.billing"), "***");
+    assert_eq!(string("image_max_dimensions"), "1920x1080");
+    assert_eq!(string("rate_limit.login"), "10/min");
+    assert_eq!(
+      string("auth_api_session_secret_expires_at"),
+      "2026-01-31T00:00:00Z"
+    );
+    // Defaults are left out
+    assert!(figment.extract_inner::<String>("api_path").is_err());
+
+    let restored: DpsConfig = figment.extract().unwrap();
+    assert_eq!(restored.get_domain(), "example.com");
+    assert!(restored.get_development_mode());
+    assert_eq!(restored.get_auth_api_port(), Some(3000));
+    assert_eq!(restored.get_auth_api_session_secret(), None);
+    assert!(restored.get_auth_api_static_api_keys().is_empty());
+    assert_eq!(
+      restored.get_auth_api_session_secret_expiry(),
+      config.get_auth_api_session_secret_expiry()
+    );
+    assert_eq!(
+      restored.get_auth_api_allowed_redirect_hosts(),
+      config.get_auth_api_allowed_redirect_hosts()
+    );
+    assert_eq!(restored.get_image_max_dimensions(), (1920, 1080));
+    assert_eq!(
+      restored.get_rate_limit_overrides(),
+      config.get_rate_limit_overrides()
+    );
+
+    let figment = Figment::from(Serialized::defaults(config.serialize_with_secrets()));
+    let restored: DpsConfig = figment.extract().unwrap();
+    assert_eq!(
+      restored.get_auth_api_session_secret().as_deref(),
+      Some("s3cret")
+    );
+    assert_eq!(restored.get_auth_api_static_api_keys().len(), 1);
+
+    let nested = Figment::from(Serialized::default("a.b.c", 1));
+    assert!(nested.extract::<DpsConfig>().is_err());
+  }
+}
@@ -0,0 +1,177 @@
+//! DPS services and the dependency graph declared between them.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// A service that can declare or be the target of dependencies in
+/// `DPS_DEPENDENCIES_<SERVICE>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DpsService {
+  /// The auth API (`auth_api`), configured by the `DPS_AUTH_API_*`
+  /// variables.
+  AuthApi,
+  /// The image processing proxy (`imageproxy`), configured by
+  /// `DPS_IMAGEPROXY_URL`.
+  ImageProxy,
+  /// The leaderboard API (`leaderboard_api`), configured by the
+  /// `DPS_LEADERBOARD_API_*` variables.
+  LeaderboardApi,
+  /// The telemetry ingest service (`telemetry_ingest`), configured by the
+  /// `DPS_TELEMETRY_INGEST_*` variables.
+  TelemetryIngest,
+  /// The notifications API (`notifications_api`), configured by the
+  /// `DPS_NOTIFICATIONS_API_*` variables.
+  NotificationsApi,
+  /// A service or backing store this crate does not configure, such as
+  /// `game_api` or `redis`, by its lowercase name.
+  Other(String),
+}
+
+impl DpsService {
+  /// Parses a service name, ignoring ASCII case: `"auth_api"`,
+  /// `"imageproxy"`, `"leaderboard_api"`, `"notifications_api"` and
+  /// `"telemetry_ingest"` are the built-in services, anything else
+  /// non-empty is [`DpsService::Other`]. Returns `None` for an empty name.
+  pub fn parse(name: &str) -> Option<DpsService> {
+    let name = name.trim().to_ascii_lowercase();
+    match name.as_str() {
+      "" => None,
+      "auth_api" => Some(DpsService::AuthApi),
+      "imageproxy" => Some(DpsService::ImageProxy),
+      "leaderboard_api" => Some(DpsService::LeaderboardApi),
+      "telemetry_ingest" => Some(DpsService::TelemetryIngest),
+      "notifications_api" => Some(DpsService::NotificationsApi),
+      _ => Some(DpsService::Other(name)),
+    }
+  }
+
+  /// Returns the name used in `DPS_DEPENDENCIES_<SERVICE>` variables.
+  pub fn as_str(&self) -> &str {
+    match self {
+      DpsService::AuthApi => "auth_api",
+      DpsService::ImageProxy => "imageproxy",
+      DpsService::LeaderboardApi => "leaderboard_api",
+      DpsService::TelemetryIngest => "telemetry_ingest",
+      DpsService::NotificationsApi => "notifications_api",
+      DpsService::Other(name) => name,
+    }
+  }
+}
+
+impl fmt::Display for DpsService {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// A service served from its own subdomain of the environment domain, like
+/// the Auth API, and how it is reached.
+pub(crate) struct SubdomainService {
+  pub(crate) service: DpsService,
+  /// Label in the startup banner.
+  pub(crate) label: &'static str,
+  /// Replaces `{service}` in the container service name template.
+  pub(crate) container_name: &'static str,
+  pub(crate) subdomain: String,
+  pub(crate) protocol: String,
+  pub(crate) port: Option<u16>,
+}
+
+/// Orders every service in `graph` (declaring services and their
+/// dependencies) so each comes after its dependencies, breaking ties by
+/// name. Returns the services of a cycle, first one repeated at the end,
+/// when there is one.
+pub(crate) fn startup_order(
+  graph: &HashMap<String, Vec<String>>,
+) -> Result<Vec<String>, Vec<String>> {
+  let mut names: Vec<&String> = graph.keys().chain(graph.values().flatten()).collect();
+  names.sort();
+  names.dedup();
+  let mut order = Vec::new();
+  let mut path = Vec::new();
+  for name in names {
+    visit(graph, name, &mut path, &mut order)?;
+  }
+  Ok(order)
+}
+
+/// Appends `name` to `order` after its dependencies, depth first. `path`
+/// holds the services being visited, to detect cycles.
+fn visit(
+  graph: &HashMap<String, Vec<String>>,
+  name: &String,
+  path: &mut Vec<String>,
+  order: &mut Vec<String>,
+) -> Result<(), Vec<String>> {
+  if order.contains(name) {
+    return Ok(());
+  }
+  if let Some(start) = path.iter().position(|visiting| visiting == name) {
+    let mut cycle = path[start..].to_vec();
+    cycle.push(name.clone());
+    return Err(cycle);
+  }
+  path.push(name.clone());
+  let mut dependencies: Vec<&String> = graph.get(name).into_iter().flatten().collect();
+  dependencies.sort();
+  for dependency in dependencies {
+    visit(graph, dependency, path, order)?;
+  }
+  path.pop();
+  order.push(name.clone());
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+    edges
+      .iter()
+      .map(|(name, deps)| {
+        (
+          name.to_string(),
+          deps.iter().map(|d| d.to_string()).collect(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(DpsService::parse("AUTH_API"), Some(DpsService::AuthApi));
+    assert_eq!(
+      DpsService::parse("imageproxy"),
+      Some(DpsService::ImageProxy)
+    );
+    assert_eq!(
+      DpsService::parse(" Redis "),
+      Some(DpsService::Other("redis".to_string()))
+    );
+    assert_eq!(
+      DpsService::parse("Leaderboard_API"),
+      Some(DpsService::LeaderboardApi)
+    );
+    assert_eq!(DpsService::parse(""), None);
+    assert_eq!(DpsService::Other("redis".to_string()).to_string(), "redis");
+  }
+
+  #[test]
+  fn test_startup_order() {
+    let order = startup_order(&graph(&[
+      ("game_api", &["redis", "auth_api"]),
+      ("auth_api", &[]),
+      ("chat", &["game_api"]),
+    ]))
+    .unwrap();
+    assert_eq!(order, ["auth_api", "redis", "game_api", "chat"]);
+
+    let cycle = startup_order(&graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["b"])])).unwrap_err();
+    assert_eq!(cycle, ["b", "c", "b"]);
+    assert_eq!(
+      startup_order(&graph(&[("a", &["a"])])).unwrap_err(),
+      ["a", "a"]
+    );
+  }
+}
@@ -0,0 +1,61 @@
+//! Algorithm used to sign Auth API session cookies.
+
+use std::fmt;
+
+/// How the Auth API signs session cookies with `DPS_AUTH_API_SESSION_SECRET`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SigningAlgorithm {
+  /// HMAC-SHA256, keyed with at least 32 bytes.
+  #[default]
+  HmacSha256,
+  /// HMAC-SHA512, keyed with at least 64 bytes.
+  HmacSha512,
+  /// Ed25519, keyed with a 32-byte private key seed.
+  Ed25519,
+}
+
+impl SigningAlgorithm {
+  /// Parses the environment variable form (`"hmac-sha256"`, `"hmac-sha512"`,
+  /// `"ed25519"`), returning `None` for anything else.
+  pub fn parse(value: &str) -> Option<SigningAlgorithm> {
+    match value {
+      "hmac-sha256" => Some(SigningAlgorithm::HmacSha256),
+      "hmac-sha512" => Some(SigningAlgorithm::HmacSha512),
+      "ed25519" => Some(SigningAlgorithm::Ed25519),
+      _ => None,
+    }
+  }
+
+  /// Returns the environment variable form of the algorithm.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SigningAlgorithm::HmacSha256 => "hmac-sha256",
+      SigningAlgorithm::HmacSha512 => "hmac-sha512",
+      SigningAlgorithm::Ed25519 => "ed25519",
+    }
+  }
+
+  /// Returns whether key material of `len` bytes suits the algorithm: at
+  /// least the digest size for HMAC, exactly 32 bytes for Ed25519.
+  pub fn accepts_key_len(&self, len: usize) -> bool {
+    match self {
+      SigningAlgorithm::HmacSha256 => len >= 32,
+      SigningAlgorithm::HmacSha512 => len >= 64,
+      SigningAlgorithm::Ed25519 => len == 32,
+    }
+  }
+
+  pub(crate) fn key_len_requirement(&self) -> &'static str {
+    match self {
+      SigningAlgorithm::HmacSha256 => "must be at least 32 bytes for hmac-sha256",
+      SigningAlgorithm::HmacSha512 => "must be at least 64 bytes for hmac-sha512",
+      SigningAlgorithm::Ed25519 => "must be exactly 32 bytes for ed25519",
+    }
+  }
+}
+
+impl fmt::Display for SigningAlgorithm {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
@@ -0,0 +1,174 @@
+//! Decrypting sops-encrypted YAML and JSON config files with the `sops` CLI
+//! (`sops` feature). Keys come from the usual sops setup
+//! (`SOPS_AGE_KEY_FILE`, cloud KMS credentials, `.sops.yaml`).
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::ConfigError;
+
+/// Returns whether `contents` is a sops-encrypted YAML or JSON document,
+/// recognized by its top-level `sops` metadata key.
+pub(crate) fn is_encrypted(contents: &str) -> bool {
+  match is_json(contents) {
+    true => json_has_top_level_sops(contents),
+    false => contents.lines().any(|line| line.starts_with("sops:")),
+  }
+}
+
+fn is_json(contents: &str) -> bool {
+  contents.trim_start().starts_with('{')
+}
+
+/// Scans the JSON object in `contents` for a `"sops"` key directly inside
+/// the outermost braces, skipping string contents and nested values.
+fn json_has_top_level_sops(contents: &str) -> bool {
+  let mut depth = 0usize;
+  let mut chars = contents.char_indices();
+  while let Some((start, c)) = chars.next() {
+    match c {
+      '{' | '[' => depth += 1,
+      '}' | ']' => depth = depth.saturating_sub(1),
+      '"' => {
+        let mut end = contents.len();
+        let mut escaped = false;
+        for (i, c) in chars.by_ref() {
+          match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => {
+              end = i;
+              break;
+            }
+            _ => {}
+          }
+        }
+        let is_key = contents[(end + 1).min(contents.len())..]
+          .trim_start()
+          .starts_with(':');
+        if depth == 1 && is_key && &contents[start + 1..end] == "sops" {
+          return true;
+        }
+      }
+      _ => {}
+    }
+  }
+  false
+}
+
+/// Decrypts `contents`, read and checksum-verified from `path`, by piping
+/// them to `sops --decrypt` so the file is not read a second time. Returns
+/// the plaintext document in the same format.
+pub(crate) fn decrypt(path: &Path, contents: &str) -> Result<String, ConfigError> {
+  run("sops", path, contents)
+}
+
+fn run(program: &str, path: &Path, contents: &str) -> Result<String, ConfigError> {
+  let error = |reason: String| ConfigError::FileRead {
+    path: path.to_path_buf(),
+    reason,
+  };
+  let format = match is_json(contents) {
+    true => "json",
+    false => "yaml",
+  };
+  let mut child = Command::new(program)
+    .args(["--decrypt", "--input-type", format, "--output-type", format])
+    .arg("/dev/stdin")
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|err| error(format!("cannot run sops: {err}")))?;
+  let mut stdin = child.stdin.take().expect("stdin is piped");
+  let input = contents.to_string();
+  // Written from a thread so a large document cannot deadlock against a
+  // full stdout pipe
+  let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+  let output = child
+    .wait_with_output()
+    .map_err(|err| error(format!("cannot run sops: {err}")))?;
+  let written = writer.join().expect("stdin writer panicked");
+  if !output.status.success() {
+    return Err(error(format!(
+      "sops: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+  written.map_err(|err| error(format!("cannot write to sops: {err}")))?;
+  String::from_utf8(output.stdout)
+    .map_err(|_| error("decrypted contents are not UTF-8".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_encrypted() {
+    let yaml = "domain: ENC[AES256_GCM,data:abc,type:str]\nsops:\n  age:\n    - recipient: age1xyz\n  version: 3.9.0\n";
+    assert!(is_encrypted(yaml));
+    let json = r#"{"domain": "ENC[AES256_GCM,data:abc,type:str]", "sops": {"version": "3.9.0"}}"#;
+    assert!(is_encrypted(json));
+    assert!(is_encrypted(
+      r#"{"note": "a \"quoted\" value", "sops" : {}}"#
+    ));
+
+    assert!(!is_encrypted("domain: example.com\nauth_api:\n  sops: 1\n"));
+    assert!(!is_encrypted(r#"{"domain": "sops.example.com"}"#));
+    assert!(!is_encrypted(
+      r#"{"auth_api": {"sops": {"version": "3.9.0"}}}"#
+    ));
+    assert!(!is_encrypted(
+      r#"{"domain": "\"sops\": 1", "list": [{"sops": 1}]}"#
+    ));
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_decrypt() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("dps-config-sops-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    // Stands in for sops: echoes its arguments, then the document on stdin
+    let fake = dir.join("sops");
+    fs::write(&fake, "#!/bin/sh\necho \"$@\"\ncat\n").unwrap();
+    fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+    let failing = dir.join("sops-failing");
+    fs::write(
+      &failing,
+      "#!/bin/sh\necho 'no key could decrypt' >&2\nexit 1\n",
+    )
+    .unwrap();
+    fs::set_permissions(&failing, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = Path::new("dps.json");
+
+    let json = r#"{"sops": {}}"#;
+    assert_eq!(
+      run(fake.to_str().unwrap(), path, json).unwrap(),
+      format!("--decrypt --input-type json --output-type json /dev/stdin\n{json}")
+    );
+    let yaml = "sops:\n  version: 3.9.0\n";
+    assert_eq!(
+      run(fake.to_str().unwrap(), path, yaml).unwrap(),
+      format!("--decrypt --input-type yaml --output-type yaml /dev/stdin\n{yaml}")
+    );
+
+    let err = run(failing.to_str().unwrap(), path, json).unwrap_err();
+    assert_eq!(
+      err,
+      ConfigError::FileRead {
+        path: path.to_path_buf(),
+        reason: "sops: no key could decrypt".to_string(),
+      }
+    );
+    let err = run(dir.join("missing").to_str().unwrap(), path, json).unwrap_err();
+    assert!(err.to_string().contains("cannot run sops"), "{err}");
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,209 @@
+//! Loading `dps.toml` files by mapping their keys onto the `DPS_*`
+//! environment variables read by [`DpsConfig`](crate::DpsConfig).
+//!
+//! Only the subset of TOML needed for flat settings is supported: `[table]`
+//! headers, `key = value` pairs and `#` comments, with basic and literal
+//! strings, integers, floats, booleans and bare RFC 3339 date-times as
+//! values.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::env_file;
+use crate::env_snapshot::EnvVars;
+use crate::ConfigError;
+
+/// Reads `path` (verifying its checksum like env files), and the files it
+/// `extends`, and returns the equivalent environment variables.
+pub(crate) fn read(path: &Path) -> Result<EnvVars, ConfigError> {
+  env_file::read_extending(path, vars)
+}
+
+/// Converts TOML `contents` into environment variables: `key` in table
+/// `[auth_api]` becomes `DPS_AUTH_API_KEY`, and booleans become `"Y"` or
+/// `"N"`. Errors carry the 1-based line number.
+pub(crate) fn vars(contents: &str) -> Result<EnvVars, (usize, &'static str)> {
+  let mut vars = EnvVars::new();
+  let mut table = String::new();
+  for (index, line) in contents.lines().enumerate() {
+    let error = |reason| (index + 1, reason);
+    let line = strip_comment(line).trim();
+    if line.is_empty() {
+      continue;
+    }
+    if let Some(header) = line.strip_prefix('[') {
+      let name = header
+        .strip_suffix(']')
+        .filter(|name| is_key(name.trim()))
+        .ok_or_else(|| error("invalid table header"))?;
+      table = name.trim().replace('.', "_");
+      continue;
+    }
+    let (key, value) = line
+      .split_once('=')
+      .ok_or_else(|| error("expected `key = value`"))?;
+    let key = key.trim();
+    if !is_key(key) {
+      return Err(error("invalid key"));
+    }
+    let value = parse_value(value.trim()).ok_or_else(|| error("unsupported value"))?;
+    let name = match table.as_str() {
+      "" => format!("DPS_{key}"),
+      table => format!("DPS_{table}_{key}"),
+    };
+    vars.insert(
+      OsString::from(name.replace('.', "_").to_ascii_uppercase()),
+      OsString::from(value),
+    );
+  }
+  Ok(vars)
+}
+
+fn is_key(key: &str) -> bool {
+  !key.is_empty()
+    && key
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.'))
+}
+
+/// Removes a trailing `#` comment that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+  let mut quote = None;
+  let mut escaped = false;
+  for (i, c) in line.char_indices() {
+    match (quote, c) {
+      (Some('"'), '\\') if !escaped => {
+        escaped = true;
+        continue;
+      }
+      (Some(q), c) if c == q && !escaped => quote = None,
+      (None, '"' | '\'') => quote = Some(c),
+      (None, '#') => return &line[..i],
+      _ => {}
+    }
+    escaped = false;
+  }
+  line
+}
+
+fn parse_value(value: &str) -> Option<String> {
+  if let Some(literal) = value.strip_prefix('\'') {
+    return literal
+      .strip_suffix('\'')
+      .filter(|s| !s.contains('\''))
+      .map(str::to_string);
+  }
+  if let Some(basic) = value.strip_prefix('"') {
+    return unescape(basic.strip_suffix('"')?);
+  }
+  match value {
+    "true" => return Some("Y".to_string()),
+    "false" => return Some("N".to_string()),
+    _ => {}
+  }
+  let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+  if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit() || b == b'_') {
+    return Some(value.trim_start_matches('+').replace('_', ""));
+  }
+  // Floats such as `0.25` or `1e-3`
+  let float = value.trim_start_matches('+').replace('_', "");
+  if digits.starts_with(|c: char| c.is_ascii_digit())
+    && !value.contains(':')
+    && float.parse::<f64>().is_ok()
+  {
+    return Some(float);
+  }
+  // Bare offset date-times such as `2026-01-31T00:00:00Z`
+  let is_datetime = value.len() >= 20
+    && value.as_bytes()[0].is_ascii_digit()
+    && value
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b':' | b'.' | b'+'));
+  is_datetime.then(|| value.to_string())
+}
+
+fn unescape(value: &str) -> Option<String> {
+  let mut out = String::with_capacity(value.len());
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => return None,
+      '\\' => out.push(match chars.next()? {
+        '"' => '"',
+        '\\' => '\\',
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        _ => return None,
+      }),
+      c => out.push(c),
+    }
+  }
+  Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn var(parsed: &EnvVars, key: &str) -> Option<String> {
+    parsed
+      .get(&OsString::from(key))
+      .map(|v| v.to_string_lossy().into_owned())
+  }
+
+  #[test]
+  fn test_vars() {
+    let contents = r#"
+# Global settings
+domain = "example.com" # trailing comment
+development_mode = true
+trace_sample_ratio = 0.25
+log_sampling_rate = 1e-1
+
+[auth_api]
+port = 3_000
+session_secret = 'a#b'
+session_secret_expires_at = 2026-01-31T00:00:00Z
+insecure_cookie = false
+
+[rate_limit]
+login = "10/min"
+"#;
+    let parsed = vars(contents).unwrap();
+    assert_eq!(var(&parsed, "DPS_DOMAIN").as_deref(), Some("example.com"));
+    assert_eq!(var(&parsed, "DPS_DEVELOPMENT_MODE").as_deref(), Some("Y"));
+    assert_eq!(
+      var(&parsed, "DPS_TRACE_SAMPLE_RATIO").as_deref(),
+      Some("0.25")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_LOG_SAMPLING_RATE").as_deref(),
+      Some("1e-1")
+    );
+    assert_eq!(var(&parsed, "DPS_AUTH_API_PORT").as_deref(), Some("3000"));
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET").as_deref(),
+      Some("a#b")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET_EXPIRES_AT").as_deref(),
+      Some("2026-01-31T00:00:00Z")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_INSECURE_COOKIE").as_deref(),
+      Some("N")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_RATE_LIMIT_LOGIN").as_deref(),
+      Some("10/min")
+    );
+
+    assert_eq!(vars("domain").unwrap_err(), (1, "expected `key = value`"));
+    assert_eq!(
+      vars("\nports = [1, 2]").unwrap_err(),
+      (2, "unsupported value")
+    );
+    assert_eq!(vars("[auth api]").unwrap_err(), (1, "invalid table header"));
+  }
+}
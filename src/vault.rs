@@ -0,0 +1,50 @@
+//! Fetching secrets from a HashiCorp Vault KV v2 engine with the `curl` CLI
+//! (`vault` feature). The token is passed to `curl` on stdin so it never
+//! shows up in process lists.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::json;
+
+/// Reads the latest version of the secret at `path` in the KV v2 engine
+/// mounted at `mount`, returning its key/value pairs.
+pub(crate) fn read_kv(
+  addr: &str,
+  token: &str,
+  mount: &str,
+  path: &str,
+) -> io::Result<Vec<(String, String)>> {
+  let url = format!(
+    "{}/v1/{}/data/{}",
+    addr.trim_end_matches('/'),
+    mount.trim_matches('/'),
+    path.trim_matches('/')
+  );
+  let mut child = Command::new("curl")
+    .args(["--silent", "--show-error", "--fail", "--header", "@-"])
+    .arg(&url)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()?;
+  if let Some(mut stdin) = child.stdin.take() {
+    writeln!(stdin, "X-Vault-Token: {token}")?;
+  }
+  let output = child.wait_with_output()?;
+  if !output.status.success() {
+    return Err(io::Error::other(format!(
+      "{url}: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    )));
+  }
+
+  let body = String::from_utf8(output.stdout)
+    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{url}: not UTF-8")))?;
+  json::members_at(&body, &["data", "data"]).map_err(|(line, reason)| {
+    io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!("{url}: line {line}: {reason}"),
+    )
+  })
+}
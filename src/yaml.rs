@@ -0,0 +1,196 @@
+//! Loading YAML config files with the same key layout as `dps.toml`: keys map
+//! onto the `DPS_*` environment variables read by
+//! [`DpsConfig`](crate::DpsConfig).
+//!
+//! Only block mappings up to one level deep are supported, with plain,
+//! single- and double-quoted scalars. Sequences, flow collections, anchors
+//! and multi-line scalars are rejected.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::env_file;
+use crate::env_snapshot::EnvVars;
+use crate::ConfigError;
+
+/// Reads `path` (verifying its checksum like env files), and the files it
+/// `extends`, and returns the equivalent environment variables.
+pub(crate) fn read(path: &Path) -> Result<EnvVars, ConfigError> {
+  env_file::read_extending(path, vars)
+}
+
+/// Converts YAML `contents` into environment variables: `port` nested under
+/// `auth_api:` becomes `DPS_AUTH_API_PORT`, booleans become `"Y"` or `"N"`
+/// and null values are left out. Errors carry the 1-based line number.
+pub(crate) fn vars(contents: &str) -> Result<EnvVars, (usize, &'static str)> {
+  let mut vars = EnvVars::new();
+  // The open mapping and the indentation of its entries, once known
+  let mut section: Option<(String, Option<usize>)> = None;
+  for (index, line) in contents.lines().enumerate() {
+    let error = |reason| (index + 1, reason);
+    let content = strip_comment(line).trim_end();
+    let trimmed = content.trim_start();
+    if trimmed.is_empty() || (index == 0 && trimmed == "---") {
+      continue;
+    }
+    let indent = content.len() - trimmed.len();
+    let (key, value) = trimmed
+      .split_once(':')
+      .filter(|(_, value)| value.is_empty() || value.starts_with(' '))
+      .ok_or_else(|| error("expected `key: value`"))?;
+    if !is_key(key) {
+      return Err(error("invalid key"));
+    }
+    let value = value.trim();
+
+    let name = if indent == 0 {
+      section = None;
+      format!("DPS_{key}")
+    } else {
+      let (parent, child_indent) = section
+        .as_mut()
+        .ok_or_else(|| error("unexpected indentation"))?;
+      if *child_indent.get_or_insert(indent) != indent {
+        return Err(error("unexpected indentation"));
+      }
+      format!("DPS_{parent}_{key}")
+    };
+    if value.is_empty() {
+      if indent != 0 {
+        return Err(error("mappings may only be nested one level deep"));
+      }
+      section = Some((key.to_string(), None));
+      continue;
+    }
+    if let Some(value) = parse_value(value).ok_or_else(|| error("unsupported value"))? {
+      vars.insert(
+        OsString::from(name.to_ascii_uppercase()),
+        OsString::from(value),
+      );
+    }
+  }
+  Ok(vars)
+}
+
+fn is_key(key: &str) -> bool {
+  !key.is_empty()
+    && key
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+}
+
+/// Removes a `#` comment (preceded by whitespace) that is not inside quotes.
+fn strip_comment(line: &str) -> &str {
+  let mut quote = None;
+  let mut prev = ' ';
+  for (i, c) in line.char_indices() {
+    match (quote, c) {
+      (Some(q), c) if c == q => quote = None,
+      (None, '"' | '\'') => quote = Some(c),
+      (None, '#') if prev.is_whitespace() => return &line[..i],
+      _ => {}
+    }
+    prev = c;
+  }
+  line
+}
+
+/// Parses a scalar, returning `Some(None)` for null.
+fn parse_value(value: &str) -> Option<Option<String>> {
+  if let Some(single) = value.strip_prefix('\'') {
+    return Some(Some(single.strip_suffix('\'')?.replace("''", "'")));
+  }
+  if let Some(double) = value.strip_prefix('"') {
+    return unescape(double.strip_suffix('"')?).map(Some);
+  }
+  if value.starts_with(['[', '{', '-', '&', '*', '|', '>', '!']) {
+    return None;
+  }
+  Some(match value {
+    "~" | "null" | "Null" | "NULL" => None,
+    "true" | "True" | "TRUE" => Some("Y".to_string()),
+    "false" | "False" | "FALSE" => Some("N".to_string()),
+    value => Some(value.to_string()),
+  })
+}
+
+fn unescape(value: &str) -> Option<String> {
+  let mut out = String::with_capacity(value.len());
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' => return None,
+      '\\' => out.push(match chars.next()? {
+        '"' => '"',
+        '\\' => '\\',
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        _ => return None,
+      }),
+      c => out.push(c),
+    }
+  }
+  Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn var(parsed: &EnvVars, key: &str) -> Option<String> {
+    parsed
+      .get(&OsString::from(key))
+      .map(|v| v.to_string_lossy().into_owned())
+  }
+
+  #[test]
+  fn test_vars() {
+    let contents = r#"---
+# Global settings
+domain: example.com # trailing comment
+development_mode: true
+imageproxy_url: ~
+
+auth_api:
+  port: 3000
+  session_secret: "a#b"
+  session_secret_expires_at: 2026-01-31T00:00:00Z
+  insecure_cookie: false
+rate_limit:
+  login: '10/min'
+"#;
+    let parsed = vars(contents).unwrap();
+    assert_eq!(var(&parsed, "DPS_DOMAIN").as_deref(), Some("example.com"));
+    assert_eq!(var(&parsed, "DPS_DEVELOPMENT_MODE").as_deref(), Some("Y"));
+    assert_eq!(var(&parsed, "DPS_IMAGEPROXY_URL"), None);
+    assert_eq!(var(&parsed, "DPS_AUTH_API_PORT").as_deref(), Some("3000"));
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET").as_deref(),
+      Some("a#b")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_SESSION_SECRET_EXPIRES_AT").as_deref(),
+      Some("2026-01-31T00:00:00Z")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_AUTH_API_INSECURE_COOKIE").as_deref(),
+      Some("N")
+    );
+    assert_eq!(
+      var(&parsed, "DPS_RATE_LIMIT_LOGIN").as_deref(),
+      Some("10/min")
+    );
+
+    assert_eq!(vars("domain").unwrap_err(), (1, "expected `key: value`"));
+    assert_eq!(vars("ports: [1, 2]").unwrap_err(), (1, "unsupported value"));
+    assert_eq!(
+      vars("a:\n  b:\n    c: 1").unwrap_err(),
+      (2, "mappings may only be nested one level deep")
+    );
+    assert_eq!(
+      vars("a:\n  b: 1\n   c: 2").unwrap_err(),
+      (3, "unexpected indentation")
+    );
+  }
+}